@@ -0,0 +1,55 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::RngCore;
+
+/// Size of an encoding symbol
+const SYMBOL_SIZE: usize = 64;
+
+fn create_source_block_data(length: usize) -> Vec<u8> {
+    let mut output = vec![0u8; length];
+
+    // Random buffer
+    let mut rng = rand::thread_rng();
+    rng.fill_bytes(output.as_mut());
+
+    output
+}
+
+/// Encoding symbols of a block of `k` source symbols, the first 10% of the source symbols are lost
+fn encoding_symbols(k: usize) -> (Vec<u8>, Vec<(u32, Vec<u8>)>) {
+    let data = create_source_block_data(k * SYMBOL_SIZE);
+    let mut encoder = raptor_code::SourceBlockEncoder::new(&data, k);
+    let symbols = (k as u32 / 10..k as u32 * 2)
+        .map(|esi| (esi, encoder.fountain(esi)))
+        .collect();
+    (data, symbols)
+}
+
+fn decode(k: usize, length: usize, symbols: &[(u32, Vec<u8>)]) -> Vec<u8> {
+    let mut decoder = raptor_code::SourceBlockDecoder::new(k);
+    for (esi, symbol) in symbols {
+        if decoder.fully_specified() {
+            break;
+        }
+        decoder.push_encoding_symbol(symbol, *esi);
+    }
+    decoder.decode(length).unwrap()
+}
+
+fn decode_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode");
+    group.sample_size(10);
+
+    for k in [1000, 2000, 4096, 8192] {
+        let (data, symbols) = encoding_symbols(k);
+        assert!(decode(k, data.len(), &symbols) == data);
+
+        group.bench_with_input(BenchmarkId::from_parameter(k), &symbols, |b, symbols| {
+            b.iter(|| decode(black_box(k), data.len(), black_box(symbols)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, decode_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,73 @@
+use rand::RngCore;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Global allocator keeping track of the current and peak heap usage
+struct PeakAllocator {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+unsafe impl GlobalAlloc for PeakAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let current = self.current.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            self.peak.fetch_max(current, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        self.current.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAllocator = PeakAllocator {
+    current: AtomicUsize::new(0),
+    peak: AtomicUsize::new(0),
+};
+
+fn create_source_block_data(length: usize) -> Vec<u8> {
+    let mut output = vec![0u8; length];
+
+    // Random buffer
+    let mut rng = rand::thread_rng();
+    rng.fill_bytes(output.as_mut());
+
+    output
+}
+
+/// Peak heap usage (in bytes) of the encoder, not counting the source block itself
+fn encoder_peak_memory(source_block: &[u8], max_source_symbols: usize) -> usize {
+    let baseline = ALLOCATOR.current.load(Ordering::Relaxed);
+    ALLOCATOR.peak.store(baseline, Ordering::Relaxed);
+
+    let mut encoder = raptor_code::SourceBlockEncoder::new(source_block, max_source_symbols);
+    let repair = encoder.fountain(encoder.nb_source_symbols());
+    drop(repair);
+    drop(encoder);
+
+    ALLOCATOR.peak.load(Ordering::Relaxed) - baseline
+}
+
+fn main() {
+    let data = create_source_block_data(100 * 1024 * 1024);
+
+    for (name, length, max_source_symbols) in [
+        ("1MB / k=64", 1024 * 1024, 64),
+        ("10MB / k=64", 10 * 1024 * 1024, 64),
+        ("10MB / k=1024", 10 * 1024 * 1024, 1024),
+        ("100MB / k=1024", 100 * 1024 * 1024, 1024),
+    ] {
+        let peak = encoder_peak_memory(&data[..length], max_source_symbols);
+        println!(
+            "encoder peak memory {:>16}: {:>12} bytes ({:.2}x source block)",
+            name,
+            peak,
+            peak as f64 / length as f64
+        );
+    }
+}
@@ -0,0 +1,8 @@
+//!
+//! C library of raptor-code
+//!
+//! Builds `libraptor_code.a` and `libraptor_code.so` exporting the functions of `include/raptor_code.h`.
+//! The crate types are declared here rather than in raptor-code, since a `no_std` build cannot produce a static or shared library.
+//!
+
+pub use raptor_code::ffi::*;
@@ -0,0 +1,156 @@
+#ifndef RAPTOR_CODE_H
+#define RAPTOR_CODE_H
+
+/* Generated with cbindgen from src/ffi.rs, do not edit */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * Status returned by the functions of the C API
+ */
+typedef enum RaptorStatus {
+  /**
+   * Success
+   */
+  RAPTOR_STATUS_OK = 0,
+  /**
+   * A pointer argument is NULL
+   */
+  RAPTOR_STATUS_NULL_POINTER = -1,
+  /**
+   * The output buffer is too small
+   */
+  RAPTOR_STATUS_BUFFER_TOO_SMALL = -2,
+  /**
+   * Not enough encoding symbols have been received to decode the block
+   */
+  RAPTOR_STATUS_NOT_DECODABLE = -3,
+  /**
+   * Internal error
+   */
+  RAPTOR_STATUS_PANIC = -4,
+} RaptorStatus;
+
+/**
+ * Opaque handle on a source block decoder
+ */
+typedef struct RaptorDecoder RaptorDecoder;
+
+/**
+ * Opaque handle on a source block encoder
+ */
+typedef struct RaptorEncoder RaptorEncoder;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Create a source block encoder
+ *
+ * Returns NULL if `data` is NULL, `length` is 0 or `max_source_symbols` is not in 1..=8192.
+ * Blocks of less than 4 source symbols are padded with zero symbols, see [`SourceBlockEncoder::new`].
+ * The encoder must be released with [`raptor_encoder_free`].
+ *
+ * # Safety
+ *
+ * `data` must point to `length` readable bytes.
+ */
+struct RaptorEncoder *raptor_encoder_new(const uint8_t *data,
+                                         size_t length,
+                                         size_t max_source_symbols);
+
+/**
+ * Return the number of source symbols of the block, or 0 if `encoder` is NULL
+ *
+ * # Safety
+ *
+ * `encoder` must be NULL or a handle returned by [`raptor_encoder_new`].
+ */
+uint32_t raptor_encoder_nb_source_symbols(const struct RaptorEncoder *encoder);
+
+/**
+ * Generate the encoding symbol `esi` into `buffer`
+ *
+ * The length of the encoding symbol is written to `symbol_length`,
+ * including when `buffer` is too small.
+ *
+ * # Safety
+ *
+ * `encoder` must be a handle returned by [`raptor_encoder_new`],
+ * `buffer` must point to `buffer_length` writable bytes and `symbol_length` to a writable `size_t`.
+ */
+enum RaptorStatus raptor_encoder_fountain(struct RaptorEncoder *encoder,
+                                          uint32_t esi,
+                                          uint8_t *buffer,
+                                          size_t buffer_length,
+                                          size_t *symbol_length);
+
+/**
+ * Release an encoder, NULL is ignored
+ *
+ * # Safety
+ *
+ * `encoder` must be NULL or a handle returned by [`raptor_encoder_new`], and must not be used afterwards.
+ */
+void raptor_encoder_free(struct RaptorEncoder *encoder);
+
+/**
+ * Create a decoder for a source block of `nb_source_symbols` source symbols
+ *
+ * Returns NULL if `nb_source_symbols` is not in 1..=8192.
+ * Blocks of less than 4 source symbols are padded with zero symbols, see [`SourceBlockDecoder::new`].
+ * The decoder must be released with [`raptor_decoder_free`].
+ */
+struct RaptorDecoder *raptor_decoder_new(size_t nb_source_symbols);
+
+/**
+ * Push an encoding symbol to the decoder
+ *
+ * # Safety
+ *
+ * `decoder` must be a handle returned by [`raptor_decoder_new`]
+ * and `symbol` must point to `length` readable bytes.
+ */
+enum RaptorStatus raptor_decoder_push(struct RaptorDecoder *decoder,
+                                      const uint8_t *symbol,
+                                      size_t length,
+                                      uint32_t esi);
+
+/**
+ * Return true when the block can be fully decoded
+ *
+ * # Safety
+ *
+ * `decoder` must be NULL or a handle returned by [`raptor_decoder_new`].
+ */
+bool raptor_decoder_fully_specified(const struct RaptorDecoder *decoder);
+
+/**
+ * Decode the source block into `buffer`, `length` is the size of the source block in bytes
+ *
+ * # Safety
+ *
+ * `decoder` must be a handle returned by [`raptor_decoder_new`]
+ * and `buffer` must point to `length` writable bytes.
+ */
+enum RaptorStatus raptor_decoder_decode(struct RaptorDecoder *decoder,
+                                        uint8_t *buffer,
+                                        size_t length);
+
+/**
+ * Release a decoder, NULL is ignored
+ *
+ * # Safety
+ *
+ * `decoder` must be NULL or a handle returned by [`raptor_decoder_new`], and must not be used afterwards.
+ */
+void raptor_decoder_free(struct RaptorDecoder *decoder);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* RAPTOR_CODE_H */
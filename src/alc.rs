@@ -0,0 +1,470 @@
+//!
+//! ALC / LCT packet format
+//!
+//! Packets of the Asynchronous Layered Coding protocol (RFC 5775) used by FLUTE (RFC 6726) to deliver files
+//! over IP multicast. A packet is made of an LCT header (RFC 5651), the Raptor FEC Payload ID
+//! (RFC 5053 section 3.1.2) and the encoding symbols.
+//!
+//! ```text
+//!  0                   1                   2                   3
+//!  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+//! +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+//! |   V   | C |PSI|S| O |H|Res|A|B|   HDR_LEN     | Codepoint (CP)|
+//! +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+//! | Congestion Control Information (CCI, length = 32*(C+1) bits)  |
+//! +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+//! |  Transport Session Identifier (TSI, length = 32*S+16*H bits)  |
+//! +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+//! |   Transport Object Identifier (TOI, length = 32*O+16*H bits)  |
+//! +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+//! |                Header Extensions (if applicable)              |
+//! +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+//! |   Source Block Number (SBN)   |  Encoding Symbol ID (ESI)     |
+//! +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+//! |                       Encoding Symbols                        |
+//! ```
+//!
+
+use crate::error::Error;
+use crate::object::ObjectTransmissionInformation;
+use alloc::vec::Vec;
+
+/// LCT version number (V)
+const LCT_VERSION: u8 = 1;
+
+/// FLUTE version carried by EXT_FDT, RFC 6726
+const FLUTE_VERSION: u8 = 2;
+
+/// Header Extension Type of the FEC Object Transmission Information, RFC 5775 section 5.2
+const EXT_FTI: u8 = 64;
+
+/// Header Extension Type of the FDT Instance ID, RFC 6726 section 3.4.1
+const EXT_FDT: u8 = 192;
+
+/// Header Extension Type of the Transfer Object Length on 48 bits, ATSC A/331 section A.3.8.1
+const EXT_TOL_48: u8 = 67;
+
+/// Header Extension Type of the Transfer Object Length on 24 bits, ATSC A/331 section A.3.8.1
+const EXT_TOL_24: u8 = 194;
+
+/// Length of EXT_FTI in 32-bit words for the Raptor FEC scheme
+const EXT_FTI_LENGTH: usize = 4;
+
+/// Max Transfer Length of the Raptor Common FEC OTI (40 bits)
+const MAX_TRANSFER_LENGTH: u64 = (1 << 40) - 1;
+
+/// Max TSI, 32 bits and an extra half-word
+const MAX_TSI: u64 = (1 << 48) - 1;
+
+/// Max Transfer Object Length of EXT_TOL (48 bits)
+const MAX_TRANSFER_OBJECT_LENGTH: u64 = (1 << 48) - 1;
+
+/// Max FDT Instance ID (20 bits)
+const MAX_FDT_INSTANCE_ID: u32 = (1 << 20) - 1;
+
+/// Size of the Raptor FEC Payload ID (SBN and ESI)
+const FEC_PAYLOAD_ID_SIZE: usize = 4;
+
+/// FEC Encoding ID of Raptor, RFC 5053 section 3, used as default codepoint
+pub const RAPTOR_FEC_ENCODING_ID: u8 = 1;
+
+/// Transport Object Identifier of the File Delivery Table, RFC 6726 section 3.3
+pub const FDT_TOI: u64 = 0;
+
+///
+/// LCT header of an ALC packet
+///
+/// The TSI is written on 32 bits, or 48 bits when it does not fit, and the TOI on the smallest size
+/// allowed by the half-word flag (H).
+/// Congestion Control Information is limited to 32 bits (C = 0).
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LctHeader {
+    /// Congestion Control Information (CCI)
+    pub cci: u32,
+    /// Transport Session Identifier (TSI), at most 48 bits
+    pub tsi: u64,
+    /// Transport Object Identifier (TOI), [`FDT_TOI`] for the File Delivery Table
+    pub toi: u64,
+    /// Codepoint, identifies the FEC Encoding ID of the payload
+    pub codepoint: u8,
+    /// Close Session flag (A), last packet of the session
+    pub close_session: bool,
+    /// Close Object flag (B), last packet of the object
+    pub close_object: bool,
+    /// FEC Object Transmission Information, carried by the EXT_FTI header extension
+    pub fti: Option<ObjectTransmissionInformation>,
+    /// FDT Instance ID (20 bits), carried by the EXT_FDT header extension
+    pub fdt_instance_id: Option<u32>,
+    /// Transfer Object Length (48 bits), carried by the EXT_TOL header extension of ROUTE
+    pub transfer_object_length: Option<u64>,
+}
+
+impl LctHeader {
+    /// Create the header of an object `toi` of the session `tsi`, with the Raptor codepoint
+    pub fn new(tsi: u64, toi: u64) -> Self {
+        LctHeader {
+            tsi,
+            toi,
+            codepoint: RAPTOR_FEC_ENCODING_ID,
+            ..Default::default()
+        }
+    }
+
+    /// Serialize the header at the end of `output`
+    ///
+    /// # Returns
+    ///
+    /// * `Err(Error::InvalidPacket)` if a field does not fit in the header
+    /// * `Ok(())` otherwise
+    pub fn write(&self, output: &mut Vec<u8>) -> Result<(), Error> {
+        if self.tsi > MAX_TSI
+            || self
+                .fdt_instance_id
+                .is_some_and(|id| id > MAX_FDT_INSTANCE_ID)
+            || self
+                .fti
+                .is_some_and(|fti| fti.transfer_length > MAX_TRANSFER_LENGTH)
+            || self
+                .transfer_object_length
+                .is_some_and(|tol| tol > MAX_TRANSFER_OBJECT_LENGTH)
+        {
+            return Err(Error::InvalidPacket);
+        }
+
+        // The half-word flag (H) extends both the TSI and the TOI
+        let h = self.tsi > u32::MAX as u64;
+        let (tsi_size, toi_size) = match h {
+            false if self.toi > u32::MAX as u64 => (4, 8),
+            false => (4, 4),
+            true if self.toi > MAX_TSI => (6, 10),
+            true if self.toi > 0xFFFF => (6, 6),
+            true => (6, 2),
+        };
+        let o = (toi_size / 4) as u8;
+
+        let mut length = 4 + 4 + tsi_size + toi_size;
+        if self.fti.is_some() {
+            length += EXT_FTI_LENGTH * 4;
+        }
+        if self.fdt_instance_id.is_some() {
+            length += 4;
+        }
+        match self.transfer_object_length {
+            Some(0..=0xFFFFFF) => length += 4,
+            Some(_) => length += 8,
+            None => {}
+        }
+
+        output.push(LCT_VERSION << 4);
+        output.push(
+            (1 << 7)
+                | (o << 5)
+                | ((h as u8) << 4)
+                | ((self.close_session as u8) << 1)
+                | self.close_object as u8,
+        );
+        output.push((length / 4) as u8);
+        output.push(self.codepoint);
+        output.extend_from_slice(&self.cci.to_be_bytes());
+        output.extend_from_slice(&self.tsi.to_be_bytes()[8 - tsi_size..]);
+        if toi_size > 8 {
+            output.extend_from_slice(&[0; 2]);
+        }
+        output.extend_from_slice(&self.toi.to_be_bytes()[8 - toi_size.min(8)..]);
+
+        if let Some(fti) = &self.fti {
+            output.push(EXT_FTI);
+            output.push(EXT_FTI_LENGTH as u8);
+            output.extend_from_slice(&fti.transfer_length.to_be_bytes()[3..]);
+            output.push(0);
+            output.extend_from_slice(&fti.symbol_size.to_be_bytes());
+            output.extend_from_slice(&fti.nb_source_blocks.to_be_bytes());
+            output.push(fti.nb_sub_blocks);
+            output.push(fti.alignment);
+            output.extend_from_slice(&[0, 0]);
+        }
+
+        if let Some(fdt_instance_id) = self.fdt_instance_id {
+            output.push(EXT_FDT);
+            output.push((FLUTE_VERSION << 4) | (fdt_instance_id >> 16) as u8);
+            output.extend_from_slice(&(fdt_instance_id as u16).to_be_bytes());
+        }
+
+        match self.transfer_object_length {
+            Some(tol @ 0..=0xFFFFFF) => {
+                output.push(EXT_TOL_24);
+                output.extend_from_slice(&tol.to_be_bytes()[5..]);
+            }
+            Some(tol) => {
+                output.push(EXT_TOL_48);
+                output.push(2);
+                output.extend_from_slice(&tol.to_be_bytes()[2..]);
+            }
+            None => {}
+        }
+
+        Ok(())
+    }
+
+    /// Parse the header at the beginning of `data`
+    ///
+    /// Unknown header extensions are skipped.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(Error::InvalidPacket)` if the header is malformed or not supported
+    /// * `Ok((LctHeader, usize))` the header and its length in bytes
+    pub fn parse(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.len() < 4 || data[0] >> 4 != LCT_VERSION {
+            return Err(Error::InvalidPacket);
+        }
+
+        let c = (data[0] >> 2) & 0x3;
+        let s = (data[1] >> 7) as usize;
+        let o = ((data[1] >> 5) & 0x3) as usize;
+        let h = ((data[1] >> 4) & 0x1) as usize;
+        let length = data[2] as usize * 4;
+        let tsi_size = 4 * s + 2 * h;
+        let toi_size = 4 * o + 2 * h;
+        let mut offset = 8 + tsi_size + toi_size;
+        if c != 0 || offset > length || length > data.len() {
+            return Err(Error::InvalidPacket);
+        }
+
+        let mut header = LctHeader {
+            cci: u32::from_be_bytes(data[4..8].try_into().unwrap()),
+            tsi: read_uint(&data[8..8 + tsi_size]).ok_or(Error::InvalidPacket)?,
+            toi: read_uint(&data[8 + tsi_size..offset]).ok_or(Error::InvalidPacket)?,
+            codepoint: data[3],
+            close_session: (data[1] >> 1) & 0x1 == 1,
+            close_object: data[1] & 0x1 == 1,
+            fti: None,
+            fdt_instance_id: None,
+            transfer_object_length: None,
+        };
+
+        while offset < length {
+            let het = data[offset];
+            let extension_length = match het {
+                128.. => 4,
+                _ => data.get(offset + 1).map_or(0, |hel| *hel as usize * 4),
+            };
+            if extension_length == 0 || offset + extension_length > length {
+                return Err(Error::InvalidPacket);
+            }
+
+            let extension = &data[offset..offset + extension_length];
+            match het {
+                EXT_FTI => header.fti = Some(parse_fti(extension)?),
+                EXT_FDT => {
+                    if extension[1] >> 4 != FLUTE_VERSION {
+                        return Err(Error::InvalidPacket);
+                    }
+                    let fdt_instance_id =
+                        u32::from_be_bytes([0, extension[1], extension[2], extension[3]]);
+                    header.fdt_instance_id = Some(fdt_instance_id & MAX_FDT_INSTANCE_ID);
+                }
+                EXT_TOL_24 => header.transfer_object_length = read_uint(&extension[1..4]),
+                EXT_TOL_48 => {
+                    if extension_length != 8 {
+                        return Err(Error::InvalidPacket);
+                    }
+                    header.transfer_object_length = read_uint(&extension[2..8]);
+                }
+                _ => {}
+            }
+            offset += extension_length;
+        }
+
+        Ok((header, length))
+    }
+}
+
+/// Parse the EXT_FTI header extension of the Raptor FEC scheme, RFC 5053 section 3.2
+fn parse_fti(extension: &[u8]) -> Result<ObjectTransmissionInformation, Error> {
+    if extension.len() < EXT_FTI_LENGTH * 4 {
+        return Err(Error::InvalidPacket);
+    }
+
+    let fti = ObjectTransmissionInformation {
+        transfer_length: read_uint(&extension[2..7]).ok_or(Error::InvalidPacket)?,
+        symbol_size: u16::from_be_bytes([extension[8], extension[9]]),
+        nb_source_blocks: u16::from_be_bytes([extension[10], extension[11]]),
+        symbols_per_packet: 1,
+        nb_sub_blocks: extension[12],
+        alignment: extension[13],
+    };
+    if !fti.is_valid() {
+        return Err(Error::InvalidPacket);
+    }
+    Ok(fti)
+}
+
+/// Read a big-endian unsigned integer, `None` if it does not fit in 64 bits
+fn read_uint(data: &[u8]) -> Option<u64> {
+    let (high, low) = data.split_at(data.len().saturating_sub(8));
+    if high.iter().any(|byte| *byte != 0) {
+        return None;
+    }
+    Some(
+        low.iter()
+            .fold(0, |value, byte| (value << 8) | *byte as u64),
+    )
+}
+
+///
+/// ALC packet carrying encoding symbols of a source block
+///
+/// The encoding symbols are generated by [`crate::SourceBlockEncoder::fountain`] or
+/// [`crate::SourceBlockEncoder::fountain_group`], the packet only borrows them.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlcPacket<'a> {
+    /// LCT header
+    pub header: LctHeader,
+    /// Source Block Number (SBN)
+    pub sbn: u16,
+    /// Encoding Symbol ID (ESI) of the first encoding symbol
+    pub esi: u16,
+    /// Encoding symbols
+    pub payload: &'a [u8],
+}
+
+impl<'a> AlcPacket<'a> {
+    /// Create a packet carrying the encoding symbols `payload`
+    pub fn new(header: LctHeader, sbn: u16, esi: u16, payload: &'a [u8]) -> Self {
+        AlcPacket {
+            header,
+            sbn,
+            esi,
+            payload,
+        }
+    }
+
+    /// Serialize the packet
+    ///
+    /// # Returns
+    ///
+    /// * `Err(Error::InvalidPacket)` if a field of the header is out of range
+    /// * `Ok(Vec<u8>)` the packet
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut output = Vec::with_capacity(64 + self.payload.len());
+        self.header.write(&mut output)?;
+        output.extend_from_slice(&self.sbn.to_be_bytes());
+        output.extend_from_slice(&self.esi.to_be_bytes());
+        output.extend_from_slice(self.payload);
+        Ok(output)
+    }
+
+    /// Parse a packet, the payload is borrowed from `data`
+    ///
+    /// # Returns
+    ///
+    /// * `Err(Error::InvalidPacket)` if the packet is malformed or truncated
+    /// * `Ok(AlcPacket)` otherwise
+    pub fn parse(data: &'a [u8]) -> Result<Self, Error> {
+        let (header, length) = LctHeader::parse(data)?;
+        let fec_payload_id = data
+            .get(length..length + FEC_PAYLOAD_ID_SIZE)
+            .ok_or(Error::InvalidPacket)?;
+
+        Ok(AlcPacket {
+            header,
+            sbn: u16::from_be_bytes([fec_payload_id[0], fec_payload_id[1]]),
+            esi: u16::from_be_bytes([fec_payload_id[2], fec_payload_id[3]]),
+            payload: &data[length + FEC_PAYLOAD_ID_SIZE..],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::error::Error;
+
+    #[test]
+    fn test_lct_header_round_trip() {
+        let oti = crate::ObjectTransmissionInformation::new(123456, 1024, 64)
+            .unwrap()
+            .with_sub_blocks(2, 8)
+            .unwrap();
+
+        for (tsi, toi, tol) in [
+            (7, 1, None),
+            (7, 1 << 40, Some(0xFFFFFF)),
+            (1 << 40, 3, Some(1 << 24)),
+            (1 << 40, 1 << 40, None),
+            (1 << 40, u64::MAX, Some((1 << 48) - 1)),
+        ] {
+            let mut header = super::LctHeader::new(tsi, toi);
+            header.cci = 0xDEADBEEF;
+            header.close_object = true;
+            header.fti = Some(oti);
+            header.fdt_instance_id = Some(0xABCDE);
+            header.transfer_object_length = tol;
+
+            let mut data = Vec::new();
+            header.write(&mut data).unwrap();
+            assert!(data.len() % 4 == 0);
+            assert!(data[2] as usize * 4 == data.len());
+
+            let (parsed, length) = super::LctHeader::parse(&data).unwrap();
+            assert!(length == data.len());
+            assert!(parsed == header);
+        }
+    }
+
+    #[test]
+    fn test_lct_header_wire_format() {
+        let mut header = super::LctHeader::new(0x01020304, 0x05060708);
+        header.close_session = true;
+        let mut data = Vec::new();
+        header.write(&mut data).unwrap();
+        assert!(
+            data == [0x10, 0xA2, 4, 1, 0, 0, 0, 0, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08]
+        );
+    }
+
+    #[test]
+    fn test_alc_packet_round_trip() {
+        let payload = [1u8, 2, 3, 4, 5];
+        let packet = super::AlcPacket::new(super::LctHeader::new(1, 2), 3, 4, &payload);
+        let data = packet.to_bytes().unwrap();
+        assert!(super::AlcPacket::parse(&data).unwrap() == packet);
+
+        // Truncated packets
+        for length in 0..20 {
+            assert!(super::AlcPacket::parse(&data[..length]) == Err(Error::InvalidPacket));
+        }
+    }
+
+    #[test]
+    fn test_invalid_headers() {
+        let mut header = super::LctHeader::new(1 << 48, 0);
+        assert!(header.write(&mut Vec::new()) == Err(Error::InvalidPacket));
+        header.tsi = 0;
+        header.fdt_instance_id = Some(1 << 20);
+        assert!(header.write(&mut Vec::new()) == Err(Error::InvalidPacket));
+        header.fdt_instance_id = None;
+        header.transfer_object_length = Some(1 << 48);
+        assert!(header.write(&mut Vec::new()) == Err(Error::InvalidPacket));
+
+        let mut data = Vec::new();
+        super::LctHeader::new(1, 2).write(&mut data).unwrap();
+        // Unsupported LCT version
+        let mut invalid = data.clone();
+        invalid[0] = 0x20;
+        assert!(super::LctHeader::parse(&invalid) == Err(Error::InvalidPacket));
+        // Header extension of length 0
+        let mut invalid = data.clone();
+        invalid[2] += 1;
+        invalid.extend_from_slice(&[1, 0, 0, 0]);
+        assert!(super::LctHeader::parse(&invalid) == Err(Error::InvalidPacket));
+        // Unknown header extension
+        let mut valid = data.clone();
+        valid[2] += 1;
+        valid.extend_from_slice(&[200, 0, 0, 0]);
+        assert!(super::LctHeader::parse(&valid).is_ok());
+    }
+}
@@ -0,0 +1,117 @@
+use alloc::vec::Vec;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+///
+/// Authentication of encoding symbols with a shared secret key
+///
+/// The sender appends a tag to every encoding symbol, computed with HMAC-SHA256 over the transfer identifier,
+/// the source block number, the encoding symbol identifier and the symbol data, truncated to [`SymbolAuthenticator::TAG_SIZE`] bytes.
+/// The receivers reject the symbols with an invalid tag before they reach the decoding matrix,
+/// so symbols injected by an attacker on the network cannot corrupt the decoded block.
+///
+#[derive(Clone)]
+pub struct SymbolAuthenticator {
+    mac: HmacSha256,
+    transfer_id: u64,
+    sbn: u32,
+}
+
+impl SymbolAuthenticator {
+    /// Size in bytes of the tag appended to an encoding symbol
+    pub const TAG_SIZE: usize = 16;
+
+    /// Create an authenticator for the source block 0 of the transfer 0
+    ///
+    /// # Parameters
+    ///
+    /// * `key`: Secret key shared between the sender and the receivers.
+    pub fn new(key: &[u8]) -> Self {
+        SymbolAuthenticator {
+            mac: HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any size"),
+            transfer_id: 0,
+            sbn: 0,
+        }
+    }
+
+    /// Return an authenticator with the same key for the transfer `transfer_id`
+    ///
+    /// Tags are bound to the transfer identifier (the TOI of the object or a session nonce),
+    /// so that a symbol of an object cannot be replayed into another object sent with the same key.
+    pub fn for_transfer(&self, transfer_id: u64) -> Self {
+        SymbolAuthenticator {
+            mac: self.mac.clone(),
+            transfer_id,
+            sbn: self.sbn,
+        }
+    }
+
+    /// Return an authenticator with the same key for the source block `sbn`
+    ///
+    /// Tags are bound to the source block number, so that a symbol of a source block cannot be replayed into another one.
+    pub fn for_source_block(&self, sbn: u32) -> Self {
+        SymbolAuthenticator {
+            mac: self.mac.clone(),
+            transfer_id: self.transfer_id,
+            sbn,
+        }
+    }
+
+    fn mac(&self, esi: u32, symbol: &[u8]) -> HmacSha256 {
+        let mut mac = self.mac.clone();
+        mac.update(&self.transfer_id.to_be_bytes());
+        mac.update(&self.sbn.to_be_bytes());
+        mac.update(&esi.to_be_bytes());
+        mac.update(symbol);
+        mac
+    }
+
+    /// Append the tag of `symbol` at the end of the symbol
+    pub(crate) fn append(&self, esi: u32, symbol: &mut Vec<u8>) {
+        let tag = self.mac(esi, symbol).finalize().into_bytes();
+        symbol.extend_from_slice(&tag[..Self::TAG_SIZE]);
+    }
+
+    /// Verify the tag at the end of `symbol`
+    ///
+    /// # Returns
+    ///
+    /// * `None` if the tag is invalid
+    /// * `Some(usize)` the length of the symbol without its tag
+    pub(crate) fn verify(&self, esi: u32, symbol: &[u8]) -> Option<usize> {
+        let length = symbol.len().checked_sub(Self::TAG_SIZE)?;
+        let (data, tag) = symbol.split_at(length);
+        self.mac(esi, data).verify_truncated_left(tag).ok()?;
+        Some(length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    #[test]
+    fn test_authenticator() {
+        let authenticator = super::SymbolAuthenticator::new(b"secret");
+        let mut symbol = vec![1, 2, 3, 4, 5];
+        authenticator.append(7, &mut symbol);
+        assert!(symbol.len() == 5 + super::SymbolAuthenticator::TAG_SIZE);
+        assert!(authenticator.verify(7, &symbol) == Some(5));
+
+        // Wrong ESI, wrong source block, wrong transfer, wrong key
+        assert!(authenticator.verify(8, &symbol).is_none());
+        assert!(authenticator
+            .for_source_block(1)
+            .verify(7, &symbol)
+            .is_none());
+        assert!(authenticator.for_transfer(1).verify(7, &symbol).is_none());
+        assert!(super::SymbolAuthenticator::new(b"other")
+            .verify(7, &symbol)
+            .is_none());
+
+        symbol[0] ^= 1;
+        assert!(authenticator.verify(7, &symbol).is_none());
+        assert!(authenticator.verify(7, &[0; 4]).is_none());
+    }
+}
@@ -0,0 +1,367 @@
+//!
+//! CCSDS Space Packet encapsulation
+//!
+//! Encoding symbols are wrapped into telemetry Space Packets (CCSDS 133.0-B-2) of an Application Process
+//! Identifier (APID). The packet secondary header identifies the encoding symbol inside the object,
+//! so the ground segment can feed the packets to an [`ObjectDecoder`] in any order and with any loss.
+//!
+//! ```text
+//! Primary header
+//! +---------+------+---------+------------+-----------+----------------+-----------------+
+//! | Version | Type | Sec Hdr |    APID    | Seq Flags | Sequence Count | Data Length - 1 |
+//! |   (3)   | (1)  |   (1)   |    (11)    |    (2)    |      (14)      |      (16)       |
+//! +---------+------+---------+------------+-----------+----------------+-----------------+
+//! Secondary header
+//! +-------------------+-------------------+---------------------------+
+//! |     SBN (16)      |     ESI (16)      | Source Block Length (16)  |
+//! +-------------------+-------------------+---------------------------+
+//! ```
+//!
+
+use crate::error::Error;
+use crate::object::{ObjectDecoder, ObjectEncoder};
+use alloc::vec::Vec;
+
+/// Size of the packet primary header
+const PRIMARY_HEADER_SIZE: usize = 6;
+
+/// Size of the packet secondary header (SBN, ESI and source block length)
+const SECONDARY_HEADER_SIZE: usize = 6;
+
+/// Max size of the packet data field
+const MAX_DATA_FIELD_SIZE: usize = 1 << 16;
+
+/// Sequence flags of an unsegmented user data
+const SEQUENCE_FLAGS_UNSEGMENTED: u8 = 0b11;
+
+/// Modulo of the packet sequence count (14 bits)
+const SEQUENCE_COUNT_MODULO: u16 = 1 << 14;
+
+/// APID reserved for idle packets
+pub const IDLE_APID: u16 = 0x7FF;
+
+///
+/// Space Packet carrying an encoding symbol
+///
+/// Packets are telemetry packets with a secondary header and unsegmented user data.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpacePacket<'a> {
+    /// Application Process Identifier (11 bits)
+    pub apid: u16,
+    /// Packet sequence count (14 bits)
+    pub sequence_count: u16,
+    /// Source Block Number (SBN)
+    pub sbn: u16,
+    /// Encoding Symbol ID (ESI)
+    pub esi: u16,
+    /// Number of source symbols of the source block
+    pub source_block_length: u16,
+    /// Encoding symbol
+    pub payload: &'a [u8],
+}
+
+impl<'a> SpacePacket<'a> {
+    /// Serialize the packet
+    ///
+    /// # Returns
+    ///
+    /// * `Err(Error::InvalidPacket)` if the APID or the sequence count is out of range or the payload is too large
+    /// * `Ok(Vec<u8>)` the packet
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let data_field_size = SECONDARY_HEADER_SIZE + self.payload.len();
+        if self.apid > IDLE_APID
+            || self.sequence_count >= SEQUENCE_COUNT_MODULO
+            || data_field_size > MAX_DATA_FIELD_SIZE
+        {
+            return Err(Error::InvalidPacket);
+        }
+
+        let mut output = Vec::with_capacity(PRIMARY_HEADER_SIZE + data_field_size);
+        // Version 0, telemetry packet, secondary header present
+        output.extend_from_slice(&((1 << 11) | self.apid).to_be_bytes());
+        output.extend_from_slice(
+            &(((SEQUENCE_FLAGS_UNSEGMENTED as u16) << 14) | self.sequence_count).to_be_bytes(),
+        );
+        output.extend_from_slice(&((data_field_size - 1) as u16).to_be_bytes());
+        output.extend_from_slice(&self.sbn.to_be_bytes());
+        output.extend_from_slice(&self.esi.to_be_bytes());
+        output.extend_from_slice(&self.source_block_length.to_be_bytes());
+        output.extend_from_slice(self.payload);
+        Ok(output)
+    }
+
+    /// Parse a packet, the payload is borrowed from `data`
+    ///
+    /// Bytes following the packet data field are ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(Error::InvalidPacket)` if the packet is truncated or is not an unsegmented telemetry packet with a secondary header
+    /// * `Ok(SpacePacket)` otherwise
+    pub fn parse(data: &'a [u8]) -> Result<Self, Error> {
+        if data.len() < PRIMARY_HEADER_SIZE {
+            return Err(Error::InvalidPacket);
+        }
+
+        let identification = u16::from_be_bytes([data[0], data[1]]);
+        let sequence_control = u16::from_be_bytes([data[2], data[3]]);
+        let data_field_size = u16::from_be_bytes([data[4], data[5]]) as usize + 1;
+        if identification >> 11 != 0b00001
+            || (sequence_control >> 14) as u8 != SEQUENCE_FLAGS_UNSEGMENTED
+            || data_field_size < SECONDARY_HEADER_SIZE
+        {
+            return Err(Error::InvalidPacket);
+        }
+
+        let data_field = data
+            .get(PRIMARY_HEADER_SIZE..PRIMARY_HEADER_SIZE + data_field_size)
+            .ok_or(Error::InvalidPacket)?;
+        Ok(SpacePacket {
+            apid: identification & IDLE_APID,
+            sequence_count: sequence_control & (SEQUENCE_COUNT_MODULO - 1),
+            sbn: u16::from_be_bytes([data_field[0], data_field[1]]),
+            esi: u16::from_be_bytes([data_field[2], data_field[3]]),
+            source_block_length: u16::from_be_bytes([data_field[4], data_field[5]]),
+            payload: &data_field[SECONDARY_HEADER_SIZE..],
+        })
+    }
+}
+
+///
+/// Encapsulation of encoding symbols into the Space Packets of an APID
+///
+/// The packet sequence count is incremented for every packet.
+///
+#[derive(Debug, Clone)]
+pub struct SpacePacketEncoder {
+    apid: u16,
+    sequence_count: u16,
+}
+
+impl SpacePacketEncoder {
+    /// Create an encapsulator of the APID `apid`
+    ///
+    /// # Returns
+    ///
+    /// * `None` if `apid` does not fit in 11 bits or is the idle APID
+    /// * `Some(SpacePacketEncoder)` otherwise
+    pub fn new(apid: u16) -> Option<Self> {
+        if apid >= IDLE_APID {
+            return None;
+        }
+        Some(SpacePacketEncoder {
+            apid,
+            sequence_count: 0,
+        })
+    }
+
+    /// Wrap an encoding symbol into a Space Packet
+    ///
+    /// # Parameters
+    ///
+    /// * `sbn`: Source Block Number.
+    /// * `esi`: Encoding Symbol ID.
+    /// * `source_block_length`: Number of source symbols of the source block.
+    /// * `encoding_symbol`: Encoding symbol, at most 65530 bytes.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(Error::InvalidPacket)` if the encoding symbol does not fit in a Space Packet
+    /// * `Ok(Vec<u8>)` the packet
+    pub fn encode(
+        &mut self,
+        sbn: u16,
+        esi: u16,
+        source_block_length: u16,
+        encoding_symbol: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let packet = SpacePacket {
+            apid: self.apid,
+            sequence_count: self.sequence_count,
+            sbn,
+            esi,
+            source_block_length,
+            payload: encoding_symbol,
+        }
+        .to_bytes()?;
+        self.sequence_count = (self.sequence_count + 1) % SEQUENCE_COUNT_MODULO;
+        Ok(packet)
+    }
+
+    /// Generate the packets of an object
+    ///
+    /// The source symbols and `nb_repair` repair symbols of every source block are encapsulated, block after block.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(Error::InvalidPacket)` if an encoding symbol does not fit in a Space Packet
+    /// * `Ok(Vec<Vec<u8>>)` the packets
+    pub fn object_packets<D: AsRef<[u8]>>(
+        &mut self,
+        encoder: &ObjectEncoder<D>,
+        nb_repair: u32,
+    ) -> Result<Vec<Vec<u8>>, Error> {
+        let mut packets = Vec::new();
+        for sbn in 0..encoder.nb_source_blocks() {
+            let mut block_encoder = encoder.sub_block_encoder(sbn).unwrap();
+            let k = block_encoder.nb_source_symbols();
+            if k + nb_repair > u16::MAX as u32 + 1 {
+                return Err(Error::InvalidPacket);
+            }
+            for esi in 0..k + nb_repair {
+                let encoding_symbol = block_encoder.fountain(esi);
+                packets.push(self.encode(sbn as u16, esi as u16, k as u16, &encoding_symbol)?);
+            }
+        }
+        Ok(packets)
+    }
+}
+
+///
+/// Reassembly of the Space Packets of an APID into an object decoder
+///
+/// Packets of other APIDs are ignored. Gaps in the packet sequence count are accounted as lost packets.
+///
+pub struct SpacePacketDecoder<W> {
+    apid: u16,
+    decoder: ObjectDecoder<W>,
+    next_sequence_count: Option<u16>,
+    nb_received: u32,
+    nb_lost: u32,
+}
+
+impl<W: AsMut<[u8]>> SpacePacketDecoder<W> {
+    /// Reassemble the packets of the APID `apid` into `decoder`
+    ///
+    /// The object decoder is typically created from the transfer manifest of the object.
+    pub fn new(apid: u16, decoder: ObjectDecoder<W>) -> Self {
+        SpacePacketDecoder {
+            apid,
+            decoder,
+            next_sequence_count: None,
+            nb_received: 0,
+            nb_lost: 0,
+        }
+    }
+
+    /// Push a Space Packet
+    ///
+    /// Packets of other APIDs, including idle packets, are ignored from their primary header only.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(Error::InvalidPacket)` if the packet is malformed or its source block length does not match the object
+    /// * `Ok(())` otherwise
+    pub fn push(&mut self, data: &[u8]) -> Result<(), Error> {
+        let identification = data.get(..2).ok_or(Error::InvalidPacket)?;
+        if u16::from_be_bytes([identification[0], identification[1]]) & IDLE_APID != self.apid {
+            return Ok(());
+        }
+
+        let packet = SpacePacket::parse(data)?;
+        let oti = self.decoder.transmission_information();
+        if oti.nb_source_symbols_in_block(packet.sbn as u32)
+            != Some(packet.source_block_length as u32)
+        {
+            return Err(Error::InvalidPacket);
+        }
+
+        if let Some(expected) = self.next_sequence_count {
+            let gap =
+                (packet.sequence_count + SEQUENCE_COUNT_MODULO - expected) % SEQUENCE_COUNT_MODULO;
+            self.nb_lost += gap as u32;
+        }
+        self.next_sequence_count = Some((packet.sequence_count + 1) % SEQUENCE_COUNT_MODULO);
+        self.nb_received += 1;
+
+        self.decoder
+            .push_encoding_symbol(packet.sbn as u32, packet.esi as u32, packet.payload);
+        Ok(())
+    }
+
+    /// Return the number of packets of the APID received
+    pub fn nb_received_packets(&self) -> u32 {
+        self.nb_received
+    }
+
+    /// Return the number of packets lost, from the gaps in the packet sequence count
+    ///
+    /// Packets received out of order are counted as lost, packets lost before the first packet received are not.
+    pub fn nb_lost_packets(&self) -> u32 {
+        self.nb_lost
+    }
+
+    /// Return true when all the source blocks of the object are decoded
+    pub fn fully_decoded(&self) -> bool {
+        self.decoder.fully_decoded()
+    }
+
+    /// Consume the reassembler and return the decoded object, see [`ObjectDecoder::finish`]
+    pub fn finish(self) -> Result<W, Error> {
+        self.decoder.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{SpacePacket, SpacePacketEncoder};
+    use crate::error::Error;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_space_packet_wire_format() {
+        let mut encoder = SpacePacketEncoder::new(0x123).unwrap();
+        let data = encoder.encode(1, 2, 3, &[0xAA, 0xBB]).unwrap();
+        assert!(data == [0x09, 0x23, 0xC0, 0x00, 0x00, 0x07, 0, 1, 0, 2, 0, 3, 0xAA, 0xBB]);
+
+        let packet = SpacePacket::parse(&data).unwrap();
+        assert!(packet.apid == 0x123);
+        assert!(packet.sequence_count == 0);
+        assert!((packet.sbn, packet.esi, packet.source_block_length) == (1, 2, 3));
+        assert!(packet.payload == [0xAA, 0xBB]);
+
+        let data = encoder.encode(1, 3, 3, &[]).unwrap();
+        assert!(SpacePacket::parse(&data).unwrap().sequence_count == 1);
+    }
+
+    #[test]
+    fn test_sequence_count_wrap() {
+        let mut encoder = SpacePacketEncoder::new(5).unwrap();
+        encoder.sequence_count = 0x3FFF;
+        let data = encoder.encode(0, 0, 1, &[1]).unwrap();
+        assert!(SpacePacket::parse(&data).unwrap().sequence_count == 0x3FFF);
+        let data = encoder.encode(0, 1, 1, &[1]).unwrap();
+        assert!(SpacePacket::parse(&data).unwrap().sequence_count == 0);
+    }
+
+    #[test]
+    fn test_invalid_space_packets() {
+        assert!(SpacePacketEncoder::new(super::IDLE_APID).is_none());
+        let mut encoder = SpacePacketEncoder::new(1).unwrap();
+        let payload = alloc::vec![0u8; super::MAX_DATA_FIELD_SIZE - super::SECONDARY_HEADER_SIZE];
+        assert!(encoder.encode(0, 0, 1, &payload).is_ok());
+        assert!(encoder
+            .encode(0, 0, 1, &payload[..payload.len() - 1])
+            .is_ok());
+        let payload: Vec<u8> = alloc::vec![0u8; payload.len() + 1];
+        assert!(encoder.encode(0, 0, 1, &payload) == Err(Error::InvalidPacket));
+
+        let data = encoder.encode(0, 0, 1, &[1, 2, 3]).unwrap();
+        // Truncated
+        assert!(SpacePacket::parse(&data[..data.len() - 1]) == Err(Error::InvalidPacket));
+        // Telecommand packet
+        let mut invalid = data.clone();
+        invalid[0] |= 0x10;
+        assert!(SpacePacket::parse(&invalid) == Err(Error::InvalidPacket));
+        // Segmented user data
+        let mut invalid = data.clone();
+        invalid[2] &= 0x7F;
+        assert!(SpacePacket::parse(&invalid) == Err(Error::InvalidPacket));
+        // Data field shorter than the secondary header
+        let mut invalid = data.clone();
+        invalid[5] = 4;
+        assert!(SpacePacket::parse(&invalid) == Err(Error::InvalidPacket));
+    }
+}
@@ -0,0 +1,109 @@
+use alloc::vec::Vec;
+
+/// CRC-32C (Castagnoli) reversed polynomial
+const CRC32C_POLYNOMIAL: u32 = 0x82F6_3B78;
+
+/// CRC-32C lookup table, generated at compile time
+static CRC32C_TABLE: [u32; 256] = crc32c_table();
+
+const fn crc32c_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut j = 0;
+        while j < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ CRC32C_POLYNOMIAL
+            } else {
+                crc >> 1
+            };
+            j += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// Computes the CRC-32C (Castagnoli) of a slice of bytes
+pub fn crc32c(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc = CRC32C_TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+///
+/// Checksum appended to each encoding symbol
+///
+/// When a checksum is configured, the encoder appends it to every encoding symbol and the decoder
+/// verifies it before the symbol is used. Corrupted symbols are handled as erasures.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Checksum {
+    /// CRC-32C (Castagnoli), 4 bytes in network byte order
+    Crc32c,
+}
+
+impl Checksum {
+    /// Return the number of bytes appended to an encoding symbol
+    pub fn size(&self) -> usize {
+        match self {
+            Checksum::Crc32c => 4,
+        }
+    }
+
+    /// Append the checksum of `symbol` at the end of the symbol
+    pub(crate) fn append(&self, symbol: &mut Vec<u8>) {
+        match self {
+            Checksum::Crc32c => {
+                let crc = crc32c(symbol);
+                symbol.extend_from_slice(&crc.to_be_bytes());
+            }
+        }
+    }
+
+    /// Verify the checksum at the end of `symbol`
+    ///
+    /// # Returns
+    ///
+    /// * `None` if the checksum is invalid
+    /// * `Some(usize)` the length of the symbol without its checksum
+    pub(crate) fn verify(&self, symbol: &[u8]) -> Option<usize> {
+        let length = symbol.len().checked_sub(self.size())?;
+        let (data, checksum) = symbol.split_at(length);
+        let valid = match self {
+            Checksum::Crc32c => crc32c(data).to_be_bytes() == checksum,
+        };
+        valid.then_some(length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    #[test]
+    fn test_crc32c() {
+        // Test vectors of RFC 3720 appendix B.4
+        assert!(super::crc32c(&[0u8; 32]) == 0x8A9136AA);
+        assert!(super::crc32c(&[0xFFu8; 32]) == 0x62A8AB43);
+        let increasing: Vec<u8> = (0..32).collect();
+        assert!(super::crc32c(&increasing) == 0x46DD794E);
+        assert!(super::crc32c(b"123456789") == 0xE3069283);
+    }
+
+    #[test]
+    fn test_checksum_verify() {
+        let checksum = super::Checksum::Crc32c;
+        let mut symbol = vec![1, 2, 3, 4, 5];
+        checksum.append(&mut symbol);
+        assert!(symbol.len() == 5 + checksum.size());
+        assert!(checksum.verify(&symbol) == Some(5));
+
+        symbol[2] ^= 0x10;
+        assert!(checksum.verify(&symbol).is_none());
+        assert!(checksum.verify(&[1, 2]).is_none());
+    }
+}
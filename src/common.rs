@@ -1,9 +1,13 @@
 use crate::tables::{SYSTEMATIC_INDEX, V0, V1};
+use alloc::vec::Vec;
 
 /// Computes the number of intermediate symbols (L), the first prime number greater than or equal to L (L_prime),
 /// the number of LDPC symbols (S), and the number of half-symbols (H) from the number of source symbols (K),
 /// as specified in RFC section 5.4.2.3.
 ///
+/// Only integer arithmetic is used, so the parameters are identical on every platform
+/// and can be evaluated at compile time.
+///
 /// # Parameters
 ///
 /// * `k`: The number of source symbols.
@@ -17,39 +21,60 @@ use crate::tables::{SYSTEMATIC_INDEX, V0, V1};
 /// * `H`: The number of half-symbols
 /// * `H_prime`: ceil(H/2)
 ///
-pub fn intermediate_symbols(k: u32) -> (u32, u32, u32, u32, u32) {
+pub const fn intermediate_symbols(k: u32) -> (u32, u32, u32, u32, u32) {
+    let k = k as u64;
+
     // X be the smallest positive integer such that X*(X-1) >= 2*K.
-    // X^2 - X - 2k >= 0
-    // det = b^ - 4ac
-    let x = ((1f64 + f64::sqrt(1f64 + (8f64 * k as f64))) / 2f64).ceil() as u64;
+    // isqrt(2K) * (isqrt(2K) - 1) <= 2K, the search starts from there
+    let mut x = (2 * k).isqrt();
+    if x == 0 {
+        x = 1;
+    }
+    while x * (x - 1) < 2 * k {
+        x += 1;
+    }
 
     //S be the smallest prime integer such that S >= ceil(0.01*K) + X
-    let s = (0.01f64 * k as f64).ceil() as u64 + x;
-    let s = prime_greater_or_equal(s);
+    let s = prime_greater_or_equal(k.div_ceil(100) + x);
 
     // H is the smallest integer such that choose(H, ceil(H/2)) >= K + S
     let mut h = 1;
-    while choose(h, ((h as f64) / 2.0).ceil() as u64) < k as u64 + s {
+    while choose(h, h.div_ceil(2)) < k + s {
         h += 1
     }
 
-    let hp = (h as f32 / 2.0).ceil() as u32;
-    let l = k as u64 + s + h;
+    let hp = h.div_ceil(2);
+    let l = k + s + h;
     let l_prime = prime_greater_or_equal(l);
 
-    (l as u32, l_prime as u32, s as u32, h as u32, hp)
+    (l as u32, l_prime as u32, s as u32, h as u32, hp as u32)
+}
+
+/// Checks if `n` is a prime number, by trial division.
+const fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    let mut d = 2;
+    while d * d <= n {
+        if n.is_multiple_of(d) {
+            return false;
+        }
+        d += 1;
+    }
+    true
 }
 
-fn prime_greater_or_equal(p: u64) -> u64 {
+const fn prime_greater_or_equal(p: u64) -> u64 {
     let mut p = p;
-    while !primes::is_prime(p) {
+    while !is_prime(p) {
         p += 1;
     }
     p
 }
 
 ///
-/// Calculates the number of ways n objects can be chosen from among r objects without repetition.
+/// Calculates the number of ways r objects can be chosen from among n objects without repetition.
 ///
 /// # Parameters
 ///
@@ -60,22 +85,16 @@ fn prime_greater_or_equal(p: u64) -> u64 {
 ///
 /// An unsigned 64-bit integer representing the number of ways the objects can be chosen without repetition.
 ///
-/// The function uses the formula n! / (r! * (n - r)!) to calculate the result.
-fn choose(n: u64, r: u64) -> u64 {
-    factorial(n) / (factorial(r) * factorial(n - r))
-}
-
-/// Calculates the factorial of a given number `n`.
-///
-/// # Parameters
-///
-/// * `n`: The number for which the factorial needs to be calculated.
-///
-/// # Returns
-///
-/// An unsigned 64-bit integer representing the factorial of the given number `n`.
-fn factorial(n: u64) -> u64 {
-    (1..=n).product()
+/// The product n! / (r! * (n - r)!) is computed incrementally: after step i, the result is choose(n - r + i, i),
+/// so every intermediate value is an exact integer and the factorials are never computed.
+const fn choose(n: u64, r: u64) -> u64 {
+    let mut result = 1;
+    let mut i = 1;
+    while i <= r {
+        result = result * (n - r + i) / i;
+        i += 1;
+    }
+    result
 }
 
 /// Checks if a specific bit of an integer is set.
@@ -90,7 +109,7 @@ fn factorial(n: u64) -> u64 {
 /// A Boolean indicating if the specified bit of the integer is set (true) or not (false).
 ///
 pub fn bit_set(x: u32, b: u32) -> bool {
-    return (x >> b) & 1 == 1;
+    (x >> b) & 1 == 1
 }
 
 /// Generates a sequence of Gray numbers that have exactly a specified number of bits set.
@@ -105,22 +124,29 @@ pub fn bit_set(x: u32, b: u32) -> bool {
 /// A vector of 32-bit unsigned integers representing the generated Gray numbers.
 ///
 pub fn gray_sequence(length: usize, b: u32) -> Vec<u32> {
-    let mut s = vec![0u32; length];
-    let mut i = 0;
+    gray_codes(b).take(length).collect()
+}
 
-    let mut x = 0u64;
-    loop {
-        let g = (x >> 1) ^ x; // Gray code
-        if g.count_ones() == b {
-            s[i] = g as u32;
-            i += 1;
-            if i >= length {
-                break;
-            }
-        }
-        x += 1
-    }
-    s
+/// Iterates over the Gray numbers that have exactly `b` bits set, without allocation.
+pub fn gray_codes(b: u32) -> impl Iterator<Item = u32> {
+    (0u64..)
+        .map(|x| (x >> 1) ^ x) // Gray code
+        .filter(move |g| g.count_ones() == b)
+        .map(|g| g as u32)
+}
+
+/// Returns the 3 LDPC symbols that include the source symbol `i`, RFC 5053 section 5.4.2.3.
+///
+/// # Parameters
+///
+/// * `i`: Index of the source symbol.
+/// * `s`: The number of LDPC symbols.
+///
+pub fn ldpc_rows(i: u32, s: u32) -> [u32; 3] {
+    let a = 1 + (i / s) % (s - 1);
+    let b = i % s;
+    let b1 = (b + a) % s;
+    [b, b1, (b1 + a) % s]
 }
 
 /// Random Generator   
@@ -154,6 +180,7 @@ pub fn deg(v: u32) -> u32 {
         }
     }
 
+    #[cfg(feature = "log")]
     log::error!("Cannot find valid degree");
     D[D.len() - 1]
 }
@@ -186,9 +213,9 @@ fn triple(k: u32, x: u32, _l: u32, l_prime: u32) -> (u32, u32, u32) {
     // d = Deg[v]
     let d = deg(v);
     // a = 1 + Rand[Y, 1, L'-1]
-    let a = 1 + rand(y as u32, 1, (l_prime - 1) as u32);
+    let a = 1 + rand(y as u32, 1, l_prime - 1);
     // b = Rand[Y, 2, L']
-    let b = rand(y as u32, 2, l_prime as u32);
+    let b = rand(y as u32, 2, l_prime);
 
     (d, a, b)
 }
@@ -203,27 +230,35 @@ fn triple(k: u32, x: u32, _l: u32, l_prime: u32) -> (u32, u32, u32) {
 /// * `l`: The number of intermediate symbols desired (K+S+H)
 /// * `l_prime`:  The first prime number >= L
 pub fn find_lt_indices(k: u32, x: u32, l: u32, l_prime: u32) -> Vec<u32> {
+    let mut indices = Vec::new();
+    for_each_lt_index(k, x, l, l_prime, |i| indices.push(i));
+    indices.sort();
+    indices
+}
+
+///
+/// Calls `f` with each LT index, without allocation.
+///
+/// Same as [`find_lt_indices`] but the indices are not sorted.
+///
+pub fn for_each_lt_index(k: u32, x: u32, l: u32, l_prime: u32, mut f: impl FnMut(u32)) {
     let (mut d, a, mut b) = triple(k, x, l, l_prime);
     if d > l {
         d = l;
     }
 
-    let mut indices = Vec::new();
     while b >= l {
         b = (b + a) % l_prime;
     }
-    indices.push(b);
+    f(b);
 
     for _ in 1..d {
         b = (b + a) % l_prime;
         while b >= l {
             b = (b + a) % l_prime;
         }
-        indices.push(b);
+        f(b);
     }
-
-    indices.sort();
-    indices
 }
 
 ///
@@ -246,6 +281,29 @@ pub fn lt_encode(k: u32, x: u32, l: u32, l_prime: u32, c: &[Vec<u8>]) -> Vec<u8>
     block
 }
 
+///
+/// LT Encode into a buffer
+///
+/// Same as [`lt_encode`] but the encoding symbol is written into `block`.
+/// Intermediate symbols are truncated to the length of `block`.
+///
+/// # Parameters
+///
+/// * `k`: The number of source symbols.
+/// * `x`: encoding symbol number (ESI)
+/// * `l`: The number of intermediate symbols desired (K+S+H)
+/// * `l_prime`:  The first prime number >= L
+/// * `c`: A slice containing the intermediate symbols
+/// * `block`: Output buffer
+///
+pub fn lt_encode_into(k: u32, x: u32, l: u32, l_prime: u32, c: &[Vec<u8>], block: &mut [u8]) {
+    let indices = find_lt_indices(k, x, l, l_prime);
+    block.fill(0);
+    for i in indices {
+        xor_u8(block, &c[i as usize]);
+    }
+}
+
 /// Performs a bitwise exclusive or (XOR) operation on two slices of bytes.
 ///
 /// # Parameters
@@ -290,7 +348,7 @@ unsafe fn _xor_u8_avx2(row_1: &mut [u8], row_2: &[u8]) {
     xor_u8(row_1, row_2) // the function below is inlined here
 }
 
-fn xor_u8(row_1: &mut [u8], row_2: &[u8]) {
+pub fn xor_u8(row_1: &mut [u8], row_2: &[u8]) {
     for (v1, v2) in row_1.iter_mut().zip(row_2) {
         *v1 ^= *v2
     }
@@ -311,28 +369,31 @@ fn xor_u8(row_1: &mut [u8], row_2: &[u8]) {
 /// * The function assumes that the input slices are sorted.
 /// * The function modifies the input `row_1` slice in place to store the result of the symmetric difference.
 pub fn symmetric_difference(row_1: &mut Vec<u32>, row_2: &[u32]) {
+    let mut output = Vec::with_capacity(row_1.len() + row_2.len());
     let mut i = 0;
     let mut j = 0;
 
-    let jl = row_2.len();
-    while i < row_1.len() && j < jl {
+    // Linear merge of the two sorted rows
+    while i < row_1.len() && j < row_2.len() {
         let v_1 = row_1[i];
         let v_2 = row_2[j];
         if v_1 == v_2 {
             // Remove union element
-            row_1.remove(i);
+            i += 1;
             j += 1;
         } else if v_2 < v_1 {
-            row_1.insert(i, v_2);
+            output.push(v_2);
             j += 1;
-            i += 1
         } else {
+            output.push(v_1);
             i += 1;
         }
     }
 
     // Add remaining elements
-    row_1.extend(&row_2[j..]);
+    output.extend(&row_1[i..]);
+    output.extend(&row_2[j..]);
+    *row_1 = output;
 }
 
 #[cfg(test)]
@@ -468,6 +529,63 @@ mod tests {
         }
     }
 
+    /// Floating point implementation of RFC 5053 section 5.4.2.3, replaced by the integer one
+    fn intermediate_symbols_f64(k: u32) -> (u32, u32, u32, u32, u32) {
+        fn is_prime(n: u64) -> bool {
+            n >= 2
+                && (2..n)
+                    .take_while(|d| d * d <= n)
+                    .all(|d| !n.is_multiple_of(d))
+        }
+        fn prime_greater_or_equal(p: u64) -> u64 {
+            (p..).find(|p| is_prime(*p)).unwrap()
+        }
+        fn choose(n: u64, r: u64) -> u64 {
+            let factorial = |n: u64| (1..=n).product::<u64>();
+            factorial(n) / (factorial(r) * factorial(n - r))
+        }
+
+        let x = ((1f64 + f64::sqrt(1f64 + (8f64 * k as f64))) / 2f64).ceil() as u64;
+        let s = prime_greater_or_equal((0.01f64 * k as f64).ceil() as u64 + x);
+
+        let mut h = 1;
+        while choose(h, ((h as f64) / 2.0).ceil() as u64) < k as u64 + s {
+            h += 1
+        }
+
+        let hp = (h as f32 / 2.0).ceil() as u32;
+        let l = k as u64 + s + h;
+        let l_prime = prime_greater_or_equal(l);
+
+        (l as u32, l_prime as u32, s as u32, h as u32, hp)
+    }
+
+    #[test]
+    fn test_intermediate_symbols_all_k() {
+        // Evaluated at compile time
+        const PARAMETERS: (u32, u32, u32, u32, u32) = super::intermediate_symbols(10);
+        assert!(PARAMETERS == (23, 23, 7, 6, 3));
+
+        for k in crate::MIN_SOURCE_SYMBOLS_PER_BLOCK..=crate::MAX_SOURCE_SYMBOLS_PER_BLOCK {
+            assert!(super::intermediate_symbols(k) == intermediate_symbols_f64(k));
+        }
+    }
+
+    #[test]
+    fn test_choose() {
+        assert!(super::choose(4, 2) == 6);
+        assert!(super::choose(16, 8) == 12870);
+        // Factorial of 30 does not fit in 64 bits
+        assert!(super::choose(30, 15) == 155117520);
+        assert!(super::choose(7, 0) == 1);
+    }
+
+    #[test]
+    fn test_is_prime() {
+        let primes: Vec<u64> = (0..30).filter(|n| super::is_prime(*n)).collect();
+        assert!(primes == vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+    }
+
     #[test]
     fn test_lt_indices() {
         struct Test {
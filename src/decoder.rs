@@ -1,14 +1,66 @@
-use crate::{encodingsymbols::EncodingSymbol, raptor};
+use alloc::collections::BTreeSet;
+use alloc::vec;
+use alloc::vec::Vec;
+
+#[cfg(feature = "auth")]
+use crate::auth::SymbolAuthenticator;
+use crate::{checksum::Checksum, encodingsymbols::EncodingSymbol, raptor, SourceBlockEncoder};
+
+/// Max number of discarded buffers kept by the decoder for reuse
+const MAX_RECYCLED_BUFFERS: usize = 32;
+
+///
+/// Statistics of a source block decoder
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DecoderStatistics {
+    /// Number of encoding symbols pushed to the decoder
+    pub nb_received: u32,
+    /// Number of encoding symbols discarded because their checksum is invalid
+    pub nb_corrupted: u32,
+    /// Number of encoding symbols discarded because their authentication tag is invalid
+    pub nb_unauthenticated: u32,
+    /// Number of encoding symbols discarded because their ESI was already received
+    pub nb_duplicate: u32,
+    /// Number of encoding symbols discarded because they are linearly dependent on the symbols already received
+    pub nb_redundant: u32,
+    /// Rank of the decoding matrix, including the LDPC and Half constraints
+    pub rank: u32,
+    /// Number of independent equations still needed to decode the block
+    pub nb_missing: u32,
+}
+
+impl DecoderStatistics {
+    /// Return the number of encoding symbols that increased the rank of the decoding matrix
+    pub fn nb_useful(&self) -> u32 {
+        self.nb_received
+            - self.nb_corrupted
+            - self.nb_unauthenticated
+            - self.nb_duplicate
+            - self.nb_redundant
+    }
+}
 
 ///
 /// A struct that represents a source block decoder that uses Raptor codes.
 pub struct SourceBlockDecoder {
     raptor: raptor::Raptor,
+    recycled_buffers: Vec<Vec<u8>>,
+    received_esi: BTreeSet<u32>,
+    stats: DecoderStatistics,
+    checksum: Option<Checksum>,
+    #[cfg(feature = "auth")]
+    authenticator: Option<SymbolAuthenticator>,
+    nb_source_symbols: u32,
+    received_symbols: Option<Vec<(u32, Vec<u8>)>>,
 }
 
 impl SourceBlockDecoder {
     /// Create a new decoder
     ///
+    /// Blocks of less than [`crate::MIN_SOURCE_SYMBOLS_PER_BLOCK`] source symbols are padded with zero symbols,
+    /// as done by [`SourceBlockEncoder::new`].
+    ///
     /// # Arguments
     ///
     /// * `nb_source_symbols` - Number of source symbols in the block
@@ -19,18 +71,258 @@ impl SourceBlockDecoder {
     pub fn new(nb_source_symbols: usize) -> Self {
         SourceBlockDecoder {
             raptor: raptor::Raptor::new(nb_source_symbols as u32),
+            recycled_buffers: Vec::new(),
+            received_esi: BTreeSet::new(),
+            stats: DecoderStatistics::default(),
+            checksum: None,
+            #[cfg(feature = "auth")]
+            authenticator: None,
+            nb_source_symbols: nb_source_symbols as u32,
+            received_symbols: None,
         }
     }
 
+    /// Keep a copy of the received encoding symbols to verify the decoded block
+    ///
+    /// Required by [`SourceBlockDecoder::verify`] and [`SourceBlockDecoder::recover`].
+    /// The decoder keeps a copy of every encoding symbol pushed, including the redundant ones,
+    /// doubling its memory usage.
+    pub fn with_verification(mut self) -> Self {
+        self.received_symbols = Some(Vec::new());
+        self
+    }
+
+    /// Verify the checksum appended to every encoding symbol
+    ///
+    /// Encoding symbols with an invalid checksum are discarded before they reach the decoding matrix,
+    /// as if they were lost, and counted in [`DecoderStatistics::nb_corrupted`].
+    pub fn with_checksum(mut self, checksum: Checksum) -> Self {
+        self.checksum = Some(checksum);
+        self
+    }
+
+    /// Verify the authentication tag appended to every encoding symbol
+    ///
+    /// Encoding symbols with an invalid tag are rejected before they reach the decoding matrix
+    /// and counted in [`DecoderStatistics::nb_unauthenticated`].
+    #[cfg(feature = "auth")]
+    pub fn with_authenticator(mut self, authenticator: SymbolAuthenticator) -> Self {
+        self.authenticator = Some(authenticator);
+        self
+    }
+
     /// Push an encoding symbol to the decoder
     ///
+    /// The encoding symbol is copied into a recycled buffer when one is available.
+    ///
     /// # Arguments
     ///
     /// * `encoding_symbol` - A slice of u8 numbers representing the encoding symbol data
     /// * `esi` - Encoding symbol identifier (ESI)
     pub fn push_encoding_symbol(&mut self, encoding_symbol: &[u8], esi: u32) {
-        let encoding_symbol = EncodingSymbol::new(encoding_symbol, esi);
-        self.raptor.add_encoding_symbol(&encoding_symbol);
+        let encoding_symbol = match self.verify_integrity(encoding_symbol, esi, 1) {
+            Some(length) => &encoding_symbol[..length],
+            None => return,
+        };
+
+        if !self.register_esi(esi) {
+            return;
+        }
+
+        let buffer = self.copy_to_buffer(encoding_symbol);
+        self.add_encoding_symbol(buffer, esi);
+    }
+
+    /// Push a packet of `nb_symbols` consecutive encoding symbols to the decoder
+    ///
+    /// The packet is generated by [`SourceBlockEncoder::fountain_group`] and identified by the ESI
+    /// of its first symbol. The checksum and the authentication tag of the packet are verified once,
+    /// then the packet is split into `nb_symbols` encoding symbols of equal size.
+    /// Every symbol of the packet is counted in the statistics of the decoder.
+    ///
+    /// # Arguments
+    ///
+    /// * `packet` - The encoding symbols `esi..esi + nb_symbols`
+    /// * `esi` - Encoding symbol identifier (ESI) of the first encoding symbol
+    /// * `nb_symbols` - Number of encoding symbols inside the packet (G)
+    pub fn push_encoding_symbol_group(&mut self, packet: &[u8], esi: u32, nb_symbols: u32) {
+        if nb_symbols == 0 {
+            return;
+        }
+
+        let packet = match self.verify_integrity(packet, esi, nb_symbols) {
+            Some(length) => &packet[..length],
+            None => return,
+        };
+
+        if packet.len() % nb_symbols as usize != 0 {
+            #[cfg(feature = "log")]
+            log::warn!(
+                "Discard packet of {} bytes, not a multiple of {} encoding symbols",
+                packet.len(),
+                nb_symbols
+            );
+            self.stats.nb_corrupted += nb_symbols;
+            return;
+        }
+
+        let symbol_size = packet.len() / nb_symbols as usize;
+        for i in 0..nb_symbols {
+            let esi = esi.wrapping_add(i);
+            if !self.register_esi(esi) {
+                continue;
+            }
+
+            let start = i as usize * symbol_size;
+            let encoding_symbol = &packet[start..start + symbol_size];
+            let buffer = self.copy_to_buffer(encoding_symbol);
+            self.add_encoding_symbol(buffer, esi);
+        }
+    }
+
+    /// Push an encoding symbol to the decoder, taking ownership of its buffer
+    ///
+    /// The buffer is moved into the decoding matrix without copy.
+    /// If the encoding symbol does not bring new information, its buffer is kept by the decoder
+    /// and can be retrieved with [`SourceBlockDecoder::take_recycled_buffer`].
+    ///
+    /// # Arguments
+    ///
+    /// * `encoding_symbol` - A vector of u8 numbers representing the encoding symbol data
+    /// * `esi` - Encoding symbol identifier (ESI)
+    pub fn push_owned(&mut self, mut encoding_symbol: Vec<u8>, esi: u32) {
+        match self.verify_integrity(&encoding_symbol, esi, 1) {
+            Some(length) => encoding_symbol.truncate(length),
+            None => {
+                self.recycle_buffer(encoding_symbol);
+                return;
+            }
+        }
+
+        if !self.register_esi(esi) {
+            self.recycle_buffer(encoding_symbol);
+            return;
+        }
+
+        self.add_encoding_symbol(encoding_symbol, esi);
+    }
+
+    /// Push an encoding symbol to the decoder from a `Bytes` buffer
+    ///
+    /// The buffer is not copied when the `Bytes` is the unique owner of its memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `encoding_symbol` - The encoding symbol data
+    /// * `esi` - Encoding symbol identifier (ESI)
+    #[cfg(feature = "bytes")]
+    pub fn push_bytes(&mut self, encoding_symbol: bytes::Bytes, esi: u32) {
+        self.push_owned(Vec::from(encoding_symbol), esi);
+    }
+
+    /// Take a buffer of a discarded encoding symbol
+    ///
+    /// Encoding symbols that are linearly dependent on the symbols already received are discarded,
+    /// their buffers can be reused to receive the next packets.
+    ///
+    /// # Returns
+    ///
+    /// * `None` if no buffer is available
+    /// * `Some(Vec<u8>)` an empty buffer, with the capacity of a previously pushed encoding symbol
+    pub fn take_recycled_buffer(&mut self) -> Option<Vec<u8>> {
+        let mut buffer = self.recycled_buffers.pop()?;
+        buffer.clear();
+        Some(buffer)
+    }
+
+    /// Return the statistics of the decoder
+    pub fn statistics(&self) -> DecoderStatistics {
+        let rank = self.raptor.rank();
+        DecoderStatistics {
+            rank,
+            nb_missing: self.raptor.get_l() - rank,
+            ..self.stats
+        }
+    }
+
+    /// Verify the consistency of the received encoding symbols
+    ///
+    /// When more encoding symbols than needed are received, the system is overdetermined.
+    /// Each received symbol is re-encoded from the solved intermediate symbols and compared
+    /// with the received data. A mismatch reveals a corrupted symbol.
+    ///
+    /// Note that a corrupted symbol used to solve the system is usually consistent with the solution,
+    /// while the other symbols are reported as mismatching. Use [`SourceBlockDecoder::recover`] to locate it.
+    ///
+    /// # Returns
+    ///
+    /// * `None` if the block is not fully specified or the verification is not enabled with [`SourceBlockDecoder::with_verification`]
+    /// * `Some(Vec<u32>)` the ESI of the mismatching encoding symbols, empty if all the symbols are consistent
+    pub fn verify(&mut self) -> Option<Vec<u32>> {
+        let received_symbols = self.received_symbols.as_ref()?;
+        if !self.raptor.fully_specified() {
+            return None;
+        }
+
+        self.raptor.reduce();
+        Some(
+            received_symbols
+                .iter()
+                .filter(|(esi, data)| !self.raptor.is_consistent(*esi, data))
+                .map(|(esi, _)| *esi)
+                .collect(),
+        )
+    }
+
+    /// Locate a corrupted encoding symbol and decode the block without it
+    ///
+    /// The block is solved again excluding one received symbol at a time, until all the remaining symbols are consistent.
+    /// This can locate a single corrupted symbol, provided that enough redundant symbols have been received.
+    /// The cost is one decoding per received symbol in the worst case.
+    ///
+    /// # Returns
+    ///
+    /// * `None` if the block is not fully specified, the verification is not enabled or the corrupted symbols cannot be located
+    /// * `Some(Vec<u32>)` the ESI of the excluded encoding symbols, empty if all the symbols were already consistent
+    pub fn recover(&mut self) -> Option<Vec<u32>> {
+        if self.verify()?.is_empty() {
+            return Some(Vec::new());
+        }
+
+        let received_symbols = self.received_symbols.take()?;
+        for (excluded, (excluded_esi, _)) in received_symbols.iter().enumerate() {
+            let mut raptor = raptor::Raptor::new(self.nb_source_symbols);
+            let symbols = received_symbols
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| *index != excluded)
+                .map(|(_, (esi, data))| EncodingSymbol::new(data, *esi))
+                .collect::<Vec<_>>();
+
+            if !raptor.add_encoding_symbols(&symbols) {
+                continue;
+            }
+
+            raptor.reduce();
+            if symbols
+                .iter()
+                .all(|symbol| raptor.is_consistent(symbol.esi, symbol.data))
+            {
+                #[cfg(feature = "log")]
+                log::warn!("Exclude corrupted encoding symbol {}", excluded_esi);
+                let excluded_esi = *excluded_esi;
+                let mut received_symbols = received_symbols;
+                received_symbols.remove(excluded);
+                self.raptor = raptor;
+                self.received_symbols = Some(received_symbols);
+                // Allow a retransmission of the corrupted symbol
+                self.received_esi.remove(&excluded_esi);
+                return Some(vec![excluded_esi]);
+            }
+        }
+
+        self.received_symbols = Some(received_symbols);
+        None
     }
 
     /// Return true when the block can be fully decoded
@@ -38,6 +330,34 @@ impl SourceBlockDecoder {
         self.raptor.fully_specified()
     }
 
+    /// Convert the decoder into an encoder of the same source block
+    ///
+    /// The encoder reuses the intermediate symbols solved by the decoder, so any encoding symbol
+    /// can be regenerated without decoding the source block and running the precode again.
+    /// The checksum and the authenticator of the decoder are carried over to the encoder.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(SourceBlockDecoder)` the decoder itself if the block cannot be fully decoded yet
+    /// * `Ok(SourceBlockEncoder)` otherwise
+    #[allow(clippy::result_large_err)]
+    pub fn into_encoder(mut self) -> Result<SourceBlockEncoder, Self> {
+        if !self.raptor.fully_specified() {
+            return Err(self);
+        }
+
+        self.raptor.reduce();
+        let mut encoder = SourceBlockEncoder::from_raptor(self.raptor);
+        if let Some(checksum) = self.checksum {
+            encoder = encoder.with_checksum(checksum);
+        }
+        #[cfg(feature = "auth")]
+        if let Some(authenticator) = self.authenticator {
+            encoder = encoder.with_authenticator(authenticator);
+        }
+        Ok(encoder)
+    }
+
     /// Decode the source block
     ///
     ///
@@ -53,6 +373,121 @@ impl SourceBlockDecoder {
     pub fn decode(&mut self, source_block_length: usize) -> Option<Vec<u8>> {
         self.raptor.decode(source_block_length)
     }
+
+    /// Decode the source block into a buffer
+    ///
+    /// Each source symbol is written directly at its offset inside `source_block`,
+    /// the length of the buffer is the size of the source block in bytes.
+    ///
+    /// # Parameters
+    ///
+    /// * `source_block`: Buffer receiving the decoded source block.
+    ///
+    /// # Returns
+    ///
+    /// * `false` if the source block cannot be decoded
+    /// * `true` if the block is decoded into `source_block`
+    pub fn decode_into(&mut self, source_block: &mut [u8]) -> bool {
+        self.raptor.decode_into(source_block)
+    }
+
+    /// Decode the source symbols into separate buffers
+    ///
+    /// The source symbol `i` is written into `source_symbols[i]`, truncated to the length of the buffer.
+    /// This allows to scatter the source block into non-contiguous memory.
+    ///
+    /// # Parameters
+    ///
+    /// * `source_symbols`: Buffers receiving the decoded source symbols, one per source symbol.
+    ///
+    /// # Returns
+    ///
+    /// * `false` if the source block cannot be decoded
+    /// * `true` if the source symbols are decoded
+    pub fn decode_symbols_into(&mut self, source_symbols: &mut [&mut [u8]]) -> bool {
+        self.raptor.decode_symbols_into(source_symbols)
+    }
+}
+
+impl SourceBlockDecoder {
+    /// Count a packet of `nb_symbols` received encoding symbols, verify its checksum and its authentication tag
+    ///
+    /// Return the length of the packet data, or `None` if the packet must be discarded
+    #[cfg_attr(not(feature = "auth"), allow(unused_variables))]
+    fn verify_integrity(
+        &mut self,
+        encoding_symbol: &[u8],
+        esi: u32,
+        nb_symbols: u32,
+    ) -> Option<usize> {
+        self.stats.nb_received += nb_symbols;
+        let mut length = encoding_symbol.len();
+
+        if let Some(checksum) = &self.checksum {
+            match checksum.verify(encoding_symbol) {
+                Some(data_length) => length = data_length,
+                None => {
+                    #[cfg(feature = "log")]
+                    log::warn!("Discard corrupted encoding symbol");
+                    self.stats.nb_corrupted += nb_symbols;
+                    return None;
+                }
+            }
+        }
+
+        #[cfg(feature = "auth")]
+        if let Some(authenticator) = &self.authenticator {
+            match authenticator.verify(esi, &encoding_symbol[..length]) {
+                Some(data_length) => length = data_length,
+                None => {
+                    #[cfg(feature = "log")]
+                    log::warn!("Discard unauthenticated encoding symbol {}", esi);
+                    self.stats.nb_unauthenticated += nb_symbols;
+                    return None;
+                }
+            }
+        }
+
+        Some(length)
+    }
+
+    /// Return false if the ESI was already received
+    fn register_esi(&mut self, esi: u32) -> bool {
+        if !self.received_esi.insert(esi) {
+            self.stats.nb_duplicate += 1;
+            return false;
+        }
+        true
+    }
+
+    /// Copy an encoding symbol into a recycled buffer when one is available
+    fn copy_to_buffer(&mut self, encoding_symbol: &[u8]) -> Vec<u8> {
+        match self.recycled_buffers.pop() {
+            Some(mut buffer) => {
+                buffer.clear();
+                buffer.extend_from_slice(encoding_symbol);
+                buffer
+            }
+            None => encoding_symbol.to_vec(),
+        }
+    }
+
+    fn add_encoding_symbol(&mut self, encoding_symbol: Vec<u8>, esi: u32) {
+        if let Some(received_symbols) = &mut self.received_symbols {
+            received_symbols.push((esi, encoding_symbol.clone()));
+        }
+
+        if let Some(buffer) = self.raptor.add_encoding_symbol_owned(encoding_symbol, esi) {
+            self.stats.nb_redundant += 1;
+            self.recycle_buffer(buffer);
+        }
+    }
+
+    fn recycle_buffer(&mut self, buffer: Vec<u8>) {
+        if self.recycled_buffers.len() < MAX_RECYCLED_BUFFERS {
+            self.recycled_buffers.push(buffer);
+        }
+    }
 }
 
 /// Decodes a source block from a given set of available encoding symbols.
@@ -1,18 +1,41 @@
+#[cfg(feature = "auth")]
+use crate::auth::SymbolAuthenticator;
+use crate::checksum::Checksum;
 use crate::common;
 use crate::partition::Partition;
 use crate::raptor;
+use alloc::vec;
+use alloc::vec::Vec;
 
 /// A struct that represents a source block encoder that uses Raptor codes.
+///
+/// Source blocks of less than [`crate::MIN_SOURCE_SYMBOLS_PER_BLOCK`] symbols are padded with zero symbols
+/// that are never sent, the repair symbols still start at ESI k.
 pub struct SourceBlockEncoder {
     intermediate: Vec<Vec<u8>>,
+    symbol_size: usize,
+    nb_source_symbols: u32,
     k: u32,
     l: u32,
     l_prime: u32,
+    checksum: Option<Checksum>,
+    #[cfg(feature = "auth")]
+    authenticator: Option<SymbolAuthenticator>,
+    /// Source block shared with the caller, source symbols are served from it without copy
+    #[cfg(feature = "bytes")]
+    source_block: Option<(bytes::Bytes, Partition)>,
 }
 
 impl SourceBlockEncoder {
     /// Create a source block encoder, passing the list of source symbols
     ///
+    /// The source block is only borrowed: each source symbol is copied once into the
+    /// precode matrix, and the resulting intermediate symbols are moved into the encoder.
+    /// The encoder does not keep any reference to `source_block`.
+    ///
+    /// Blocks of less than [`crate::MIN_SOURCE_SYMBOLS_PER_BLOCK`] source symbols are padded with zero symbols,
+    /// the decoder created with the same number of source symbols adds the same padding.
+    ///
     /// # Parameters
     ///
     /// * `source_block`: A slice of vectors containing the source symbols.
@@ -24,22 +47,72 @@ impl SourceBlockEncoder {
     pub fn new(source_block: &[u8], max_source_symbols: usize) -> Self {
         let partition = Partition::new(source_block.len(), max_source_symbols);
         let source_block = partition.create_source_block(source_block);
-        let k = source_block.len() as u32;
-        let mut raptor = raptor::Raptor::new(k);
+        let mut raptor = raptor::Raptor::new(source_block.len() as u32);
         raptor.add_encoding_symbols(&source_block);
         raptor.reduce();
+        SourceBlockEncoder::from_raptor(raptor)
+    }
+
+    /// Create a source block encoder from a `Bytes` buffer
+    ///
+    /// The encoder keeps a reference to `source_block` instead of a copy,
+    /// so [`SourceBlockEncoder::fountain_bytes`] returns the source symbols as slices of the buffer.
+    ///
+    /// # Parameters
+    ///
+    /// * `source_block`: The source block.
+    /// * `max_source_symbols`: Max number of source symbols inside the source block
+    #[cfg(feature = "bytes")]
+    pub fn from_bytes(source_block: bytes::Bytes, max_source_symbols: usize) -> Self {
+        let mut encoder = SourceBlockEncoder::new(&source_block, max_source_symbols);
+        let partition = Partition::new(source_block.len(), max_source_symbols);
+        encoder.source_block = Some((source_block, partition));
+        encoder
+    }
 
+    /// Create an encoder from the intermediate symbols of a reduced and fully specified decoding matrix
+    pub(crate) fn from_raptor(raptor: raptor::Raptor) -> Self {
+        let nb_source_symbols = raptor.nb_source_symbols();
+        let k = raptor.get_k();
+        let l = raptor.get_l();
+        let l_prime = raptor.get_l_prime();
+        let intermediate = raptor.into_intermediate_symbols();
+        let symbol_size = intermediate.iter().map(Vec::len).max().unwrap_or(0);
         SourceBlockEncoder {
-            intermediate: raptor.intermediate_symbols().to_vec(),
+            intermediate,
+            symbol_size,
+            nb_source_symbols,
             k,
-            l: raptor.get_l(),
-            l_prime: raptor.get_l_prime(),
+            l,
+            l_prime,
+            checksum: None,
+            #[cfg(feature = "auth")]
+            authenticator: None,
+            #[cfg(feature = "bytes")]
+            source_block: None,
         }
     }
 
+    /// Append a checksum to every encoding symbol generated by the encoder
+    ///
+    /// The decoder must be configured with the same checksum.
+    pub fn with_checksum(mut self, checksum: Checksum) -> Self {
+        self.checksum = Some(checksum);
+        self
+    }
+
+    /// Append an authentication tag to every encoding symbol generated by the encoder
+    ///
+    /// The tag is appended before the checksum. The decoder must be configured with the same key.
+    #[cfg(feature = "auth")]
+    pub fn with_authenticator(mut self, authenticator: SymbolAuthenticator) -> Self {
+        self.authenticator = Some(authenticator);
+        self
+    }
+
     /// Return the number of source symbols (k) inside the block
     pub fn nb_source_symbols(&self) -> u32 {
-        self.k
+        self.nb_source_symbols
     }
 
     /// Generates an encoding symbol with the specified Encoding Symbol Identifier (ESI).
@@ -53,17 +126,77 @@ impl SourceBlockEncoder {
     /// # Returns
     ///
     /// A tuple containing:
-    /// * `Vec<u8>` : The generated encoding symbol
+    /// * `Vec<u8>` : The generated encoding symbol, followed by its authentication tag and its checksum when configured
     pub fn fountain(&mut self, esi: u32) -> Vec<u8> {
-        let mut block = Vec::new();
-        let indices = common::find_lt_indices(self.k, esi, self.l, self.l_prime);
-        for indice in indices {
-            if indice < self.intermediate.len() as u32 {
-                common::xor(&mut block, &self.intermediate[indice as usize]);
+        let x = raptor::padded_esi(self.nb_source_symbols, esi);
+        let mut block = common::lt_encode(self.k, x, self.l, self.l_prime, &self.intermediate);
+        self.append_tags(esi, &mut block);
+        block
+    }
+
+    /// Generates an encoding symbol as a `Bytes` buffer
+    ///
+    /// When the encoder is created with [`SourceBlockEncoder::from_bytes`] and no tag is appended,
+    /// the source symbols (`esi` < k) are slices of the source block, without copy.
+    /// The other encoding symbols are generated by [`SourceBlockEncoder::fountain`].
+    #[cfg(feature = "bytes")]
+    pub fn fountain_bytes(&mut self, esi: u32) -> bytes::Bytes {
+        if let Some((source_block, partition)) = &self.source_block {
+            let range = partition.symbol_range(esi as usize);
+            if esi < self.nb_source_symbols && !self.has_tags() && range.len() == self.symbol_size {
+                return source_block.slice(range);
             }
         }
+        bytes::Bytes::from(self.fountain(esi))
+    }
 
-        block
+    /// Generates a packet of `nb_symbols` consecutive encoding symbols, starting at `esi`
+    ///
+    /// The packet is identified by the ESI of its first symbol (G symbols per packet, RFC 5053 section 4.4.1).
+    /// Every symbol is padded with zeros to the symbol size of the block, so the receiver splits the packet
+    /// into `nb_symbols` equal parts with [`crate::SourceBlockDecoder::push_encoding_symbol_group`].
+    /// The authentication tag and the checksum cover the whole packet and are appended once.
+    ///
+    /// # Parameters
+    ///
+    /// * `esi`: The Encoding Symbol Identifier (ESI) of the first encoding symbol.
+    /// * `nb_symbols`: Number of encoding symbols inside the packet (G).
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<u8>` : The encoding symbols `esi..esi + nb_symbols`, followed by the authentication tag and the checksum when configured
+    pub fn fountain_group(&mut self, esi: u32, nb_symbols: u32) -> Vec<u8> {
+        let mut packet = vec![0u8; nb_symbols as usize * self.symbol_size];
+        if self.symbol_size != 0 {
+            for (i, symbol) in packet.chunks_exact_mut(self.symbol_size).enumerate() {
+                let x = raptor::padded_esi(self.nb_source_symbols, esi.wrapping_add(i as u32));
+                common::lt_encode_into(self.k, x, self.l, self.l_prime, &self.intermediate, symbol);
+            }
+        }
+        self.append_tags(esi, &mut packet);
+        packet
+    }
+
+    /// Return true when an authentication tag or a checksum is appended to the encoding symbols
+    #[cfg(feature = "bytes")]
+    fn has_tags(&self) -> bool {
+        #[cfg(feature = "auth")]
+        if self.authenticator.is_some() {
+            return true;
+        }
+        self.checksum.is_some()
+    }
+
+    /// Append the authentication tag and the checksum of an encoding symbol or a packet
+    #[cfg_attr(not(feature = "auth"), allow(unused_variables))]
+    fn append_tags(&self, esi: u32, block: &mut Vec<u8>) {
+        #[cfg(feature = "auth")]
+        if let Some(authenticator) = &self.authenticator {
+            authenticator.append(esi, block);
+        }
+        if let Some(checksum) = &self.checksum {
+            checksum.append(block);
+        }
     }
 }
 
@@ -115,10 +248,7 @@ mod tests {
 
         // Try to decode the source block
 
-        let mut encoded_block: Vec<Option<Vec<u8>>> = encoded_block
-            .into_iter()
-            .map(|symbols| Some(symbols))
-            .collect();
+        let mut encoded_block: Vec<Option<Vec<u8>>> = encoded_block.into_iter().map(Some).collect();
 
         // Simulate loss
         encoded_block[0] = None;
@@ -130,4 +260,63 @@ mod tests {
         assert!(output.len() == input.len());
         assert!(output == input);
     }
+
+    #[test]
+    fn test_small_source_blocks() {
+        crate::tests::init();
+
+        // Blocks of 1 to 3 source symbols are padded to 4 symbols
+        for (input, max_source_symbols, k) in [
+            (vec![7u8], 10, 2),
+            (vec![7u8; 2], 10, 3),
+            (vec![5u8; 100], 1, 1),
+            (vec![5u8; 100], 2, 2),
+            (vec![5u8; 100], 3, 3),
+        ] {
+            let (encoded_block, nb_source_symbols) =
+                super::encode_source_block(&input, max_source_symbols, 4);
+            assert!(nb_source_symbols == k);
+            assert!(encoded_block.len() == k as usize + 4);
+
+            // Decode from the repair symbols only
+            let encoded_block: Vec<Option<Vec<u8>>> = encoded_block
+                .into_iter()
+                .enumerate()
+                .map(|(esi, symbol)| (esi >= k as usize).then_some(symbol))
+                .collect();
+            let output =
+                crate::decoder::decode_source_block(&encoded_block, k as usize, input.len())
+                    .unwrap();
+            assert!(output == input);
+        }
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn test_source_block_encoder_bytes() {
+        crate::tests::init();
+
+        for length in [1024usize, 1000, 1023] {
+            let input: Vec<u8> = (0..length).map(|i| (i * 13 % 251) as u8).collect();
+            let source_block = bytes::Bytes::from(input.clone());
+            let mut encoder = super::SourceBlockEncoder::from_bytes(source_block.clone(), 10);
+            let mut reference = super::SourceBlockEncoder::new(&input, 10);
+
+            let range = source_block.as_ptr_range();
+            for esi in 0..20 {
+                let encoding_symbol = encoder.fountain_bytes(esi);
+                assert!(encoding_symbol == reference.fountain(esi));
+                // Source symbols of the long partition are not copied
+                if (esi as usize) < length % 10 || (esi < 10 && length % 10 == 0) {
+                    assert!(range.contains(&encoding_symbol.as_ptr()));
+                }
+            }
+        }
+
+        // Source symbols are copied when a checksum is appended
+        let source_block = bytes::Bytes::from(vec![7u8; 100]);
+        let mut encoder = super::SourceBlockEncoder::from_bytes(source_block, 4)
+            .with_checksum(crate::Checksum::Crc32c);
+        assert!(encoder.fountain_bytes(0).len() == 25 + crate::Checksum::Crc32c.size());
+    }
 }
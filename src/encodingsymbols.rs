@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 pub struct EncodingSymbol<'a> {
     pub data: &'a [u8],
     pub esi: u32,
@@ -8,7 +10,7 @@ impl<'a> EncodingSymbol<'a> {
         EncodingSymbol { data, esi }
     }
 
-    pub fn from_option_block(block: &[Option<Vec<u8>>]) -> Vec<EncodingSymbol> {
+    pub fn from_option_block(block: &[Option<Vec<u8>>]) -> Vec<EncodingSymbol<'_>> {
         block
             .iter()
             .enumerate()
@@ -0,0 +1,30 @@
+///
+/// Errors returned by the object decoder and the parsers
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The object is not fully decoded
+    Incomplete,
+    /// The digest of the decoded object does not match the expected digest (manifest SHA-256 or FDT Content-MD5)
+    DigestMismatch,
+    /// The manifest is malformed or uses an unsupported version
+    InvalidManifest,
+    /// The packet is malformed or uses an unsupported header format
+    InvalidPacket,
+    /// The File Delivery Table is malformed
+    InvalidFdt,
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::Incomplete => write!(f, "object is not fully decoded"),
+            Error::DigestMismatch => write!(f, "object digest mismatch"),
+            Error::InvalidManifest => write!(f, "invalid manifest"),
+            Error::InvalidPacket => write!(f, "invalid packet"),
+            Error::InvalidFdt => write!(f, "invalid file delivery table"),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
@@ -0,0 +1,565 @@
+//!
+//! FLUTE File Delivery Table
+//!
+//! An FDT instance (RFC 6726 section 3.4.2) is an XML document sent on the TOI [`crate::alc::FDT_TOI`].
+//! It describes the objects of a session: their TOI, content location, length, MD5 digest
+//! and the FEC Object Transmission Information needed to configure the decoders.
+//!
+//! ```xml
+//! <?xml version="1.0" encoding="UTF-8"?>
+//! <FDT-Instance xmlns="urn:ietf:params:xml:ns:fdt" Expires="3600">
+//!   <File TOI="1" Content-Location="file.bin" Content-Length="1024" Transfer-Length="1024"
+//!         Content-MD5="..." FEC-OTI-FEC-Encoding-ID="1" FEC-OTI-Encoding-Symbol-Length="64"
+//!         FEC-OTI-Maximum-Source-Block-Length="16" FEC-OTI-Scheme-Specific-Info="AAEBAQ=="/>
+//! </FDT-Instance>
+//! ```
+//!
+//! The FEC attributes of the Raptor scheme are inherited from the FDT instance when a file does not define them.
+//!
+
+use crate::alc::RAPTOR_FEC_ENCODING_ID;
+use crate::error::Error;
+use crate::object::{ObjectDecoder, ObjectTransmissionInformation};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use md5::{Digest, Md5};
+
+/// XML namespace of the FDT instances
+const FDT_NAMESPACE: &str = "urn:ietf:params:xml:ns:fdt";
+
+/// Base64 alphabet, RFC 4648 section 4
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Return the MD5 digest of an object
+pub(crate) fn content_md5(object: &[u8]) -> [u8; 16] {
+    Md5::digest(object).into()
+}
+
+///
+/// Description of an object inside an FDT instance
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FdtFile {
+    /// Transport Object Identifier of the object
+    pub toi: u64,
+    /// URI of the object (`Content-Location`)
+    pub content_location: String,
+    /// Length of the object in bytes (`Content-Length`)
+    pub content_length: Option<u64>,
+    /// MIME type of the object (`Content-Type`)
+    pub content_type: Option<String>,
+    /// MD5 digest of the object (`Content-MD5`)
+    pub content_md5: Option<[u8; 16]>,
+    /// FEC Object Transmission Information, `None` if the object is not encoded with Raptor
+    pub oti: Option<ObjectTransmissionInformation>,
+}
+
+impl FdtFile {
+    /// Describe an object encoded with the transmission information `oti`
+    ///
+    /// The MD5 digest of the object is computed.
+    pub fn new(
+        toi: u64,
+        content_location: &str,
+        object: &[u8],
+        oti: ObjectTransmissionInformation,
+    ) -> Self {
+        FdtFile {
+            toi,
+            content_location: content_location.into(),
+            content_length: Some(object.len() as u64),
+            content_type: None,
+            content_md5: Some(content_md5(object)),
+            oti: Some(oti),
+        }
+    }
+
+    /// Create the decoder of the object described by the FDT
+    ///
+    /// The source block decoders are configured from the transmission information,
+    /// and the MD5 digest of the object is verified by [`ObjectDecoder::finish`].
+    ///
+    /// # Parameters
+    ///
+    /// * `output`: Buffer receiving the decoded object, its length must be equal to the transfer length.
+    ///
+    /// # Returns
+    ///
+    /// * `None` if the object is not encoded with Raptor or the length of `output` does not match
+    /// * `Some(ObjectDecoder)` otherwise
+    pub fn object_decoder<W: AsMut<[u8]>>(&self, output: W) -> Option<ObjectDecoder<W>> {
+        let decoder = ObjectDecoder::new(self.oti?, output)?;
+        Some(match self.content_md5 {
+            Some(md5) => decoder.with_content_md5(md5),
+            None => decoder,
+        })
+    }
+
+    fn write_xml(&self, xml: &mut String) {
+        write!(xml, "  <File TOI=\"{}\"", self.toi).unwrap();
+        write_attribute(xml, "Content-Location", &self.content_location);
+        if let Some(content_length) = self.content_length {
+            write!(xml, " Content-Length=\"{}\"", content_length).unwrap();
+        }
+        if let Some(content_type) = &self.content_type {
+            write_attribute(xml, "Content-Type", content_type);
+        }
+        if let Some(content_md5) = &self.content_md5 {
+            write_attribute(xml, "Content-MD5", &base64_encode(content_md5));
+        }
+        if let Some(oti) = &self.oti {
+            let max_source_block_length = oti.nb_source_symbols_in_block(0).unwrap_or(0);
+            let mut scheme_specific_info = Vec::with_capacity(4);
+            scheme_specific_info.extend_from_slice(&oti.nb_source_blocks.to_be_bytes());
+            scheme_specific_info.push(oti.nb_sub_blocks);
+            scheme_specific_info.push(oti.alignment);
+
+            write!(
+                xml,
+                " Transfer-Length=\"{}\" FEC-OTI-FEC-Encoding-ID=\"{}\"",
+                oti.transfer_length, RAPTOR_FEC_ENCODING_ID
+            )
+            .unwrap();
+            write!(
+                xml,
+                " FEC-OTI-Encoding-Symbol-Length=\"{}\" FEC-OTI-Maximum-Source-Block-Length=\"{}\"",
+                oti.symbol_size, max_source_block_length
+            )
+            .unwrap();
+            write_attribute(
+                xml,
+                "FEC-OTI-Scheme-Specific-Info",
+                &base64_encode(&scheme_specific_info),
+            );
+        }
+        xml.push_str("/>\n");
+    }
+
+    fn from_element(file: &Element, instance: &Element) -> Result<Self, Error> {
+        let toi = file
+            .attribute("TOI")
+            .and_then(|toi| toi.parse().ok())
+            .ok_or(Error::InvalidFdt)?;
+        let content_location = file
+            .attribute("Content-Location")
+            .ok_or(Error::InvalidFdt)?;
+        let content_length = parse_optional(file.attribute("Content-Length"))?;
+        let content_md5 = match file.attribute("Content-MD5") {
+            Some(md5) => Some(
+                base64_decode(md5)
+                    .and_then(|md5| md5.try_into().ok())
+                    .ok_or(Error::InvalidFdt)?,
+            ),
+            None => None,
+        };
+
+        // FEC attributes of the file, or of the FDT instance
+        let fec = |name: &str| file.attribute(name).or_else(|| instance.attribute(name));
+        let encoding_id: Option<u8> = parse_optional(fec("FEC-OTI-FEC-Encoding-ID"))?;
+        let oti = match encoding_id {
+            Some(RAPTOR_FEC_ENCODING_ID) => {
+                let transfer_length = parse_optional(file.attribute("Transfer-Length"))?
+                    .or(content_length)
+                    .ok_or(Error::InvalidFdt)?;
+                let symbol_size = parse_optional(fec("FEC-OTI-Encoding-Symbol-Length"))?
+                    .ok_or(Error::InvalidFdt)?;
+                let scheme_specific_info = fec("FEC-OTI-Scheme-Specific-Info")
+                    .and_then(base64_decode)
+                    .filter(|info| info.len() == 4)
+                    .ok_or(Error::InvalidFdt)?;
+
+                let oti = ObjectTransmissionInformation {
+                    transfer_length,
+                    symbol_size,
+                    nb_source_blocks: u16::from_be_bytes([
+                        scheme_specific_info[0],
+                        scheme_specific_info[1],
+                    ]),
+                    symbols_per_packet: 1,
+                    nb_sub_blocks: scheme_specific_info[2],
+                    alignment: scheme_specific_info[3],
+                };
+                if !oti.is_valid() {
+                    return Err(Error::InvalidFdt);
+                }
+                Some(oti)
+            }
+            _ => None,
+        };
+
+        Ok(FdtFile {
+            toi,
+            content_location: content_location.into(),
+            content_length,
+            content_type: file.attribute("Content-Type").map(Into::into),
+            content_md5,
+            oti,
+        })
+    }
+}
+
+///
+/// FDT instance, the list of objects of a FLUTE session
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FdtInstance {
+    /// Expiry time of the instance, 32 bits NTP seconds
+    pub expires: u32,
+    /// No new object will be added to the session
+    pub complete: bool,
+    /// Objects of the session
+    pub files: Vec<FdtFile>,
+}
+
+impl FdtInstance {
+    /// Create an empty FDT instance
+    pub fn new(expires: u32) -> Self {
+        FdtInstance {
+            expires,
+            complete: false,
+            files: Vec::new(),
+        }
+    }
+
+    /// Return the description of the object `toi`
+    pub fn file(&self, toi: u64) -> Option<&FdtFile> {
+        self.files.iter().find(|file| file.toi == toi)
+    }
+
+    /// Serialize the FDT instance
+    pub fn to_xml(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        write!(
+            xml,
+            "<FDT-Instance xmlns=\"{}\" Expires=\"{}\"",
+            FDT_NAMESPACE, self.expires
+        )
+        .unwrap();
+        if self.complete {
+            xml.push_str(" Complete=\"true\"");
+        }
+        xml.push_str(">\n");
+        for file in &self.files {
+            file.write_xml(&mut xml);
+        }
+        xml.push_str("</FDT-Instance>\n");
+        xml
+    }
+
+    /// Parse an FDT instance
+    ///
+    /// Only the attributes of the `FDT-Instance` and `File` elements are read, other elements are ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(Error::InvalidFdt)` if the document is malformed or a mandatory attribute is missing
+    /// * `Ok(FdtInstance)` otherwise
+    pub fn from_xml(xml: &str) -> Result<Self, Error> {
+        let elements = parse_elements(xml)?;
+        let instance = elements
+            .iter()
+            .find(|element| element.name == "FDT-Instance")
+            .ok_or(Error::InvalidFdt)?;
+
+        let files = elements
+            .iter()
+            .filter(|element| element.name == "File")
+            .map(|file| FdtFile::from_element(file, instance))
+            .collect::<Result<_, _>>()?;
+
+        Ok(FdtInstance {
+            expires: parse_optional(instance.attribute("Expires"))?.ok_or(Error::InvalidFdt)?,
+            complete: matches!(instance.attribute("Complete"), Some("true" | "1")),
+            files,
+        })
+    }
+}
+
+fn write_attribute(xml: &mut String, name: &str, value: &str) {
+    write!(xml, " {}=\"", name).unwrap();
+    for c in value.chars() {
+        match c {
+            '&' => xml.push_str("&amp;"),
+            '<' => xml.push_str("&lt;"),
+            '>' => xml.push_str("&gt;"),
+            '"' => xml.push_str("&quot;"),
+            c => xml.push(c),
+        }
+    }
+    xml.push('"');
+}
+
+fn parse_optional<T: core::str::FromStr>(value: Option<&str>) -> Result<Option<T>, Error> {
+    value
+        .map(|value| value.trim().parse().map_err(|_| Error::InvalidFdt))
+        .transpose()
+}
+
+/// Start tag of an XML element
+struct Element<'a> {
+    /// Name of the element without namespace prefix
+    name: &'a str,
+    /// Attributes, with the entities replaced
+    attributes: Vec<(&'a str, String)>,
+}
+
+impl Element<'_> {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| *attribute == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Read the start tags of an XML document
+///
+/// Declarations, comments, processing instructions and end tags are skipped.
+fn parse_elements(xml: &str) -> Result<Vec<Element<'_>>, Error> {
+    let mut elements = Vec::new();
+    let mut rest = xml;
+
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let skip_to = match rest.as_bytes().first() {
+            Some(b'?') => Some("?>"),
+            Some(b'!') if rest.starts_with("!--") => Some("-->"),
+            Some(b'!') | Some(b'/') => Some(">"),
+            _ => None,
+        };
+        if let Some(end) = skip_to {
+            let position = rest.find(end).ok_or(Error::InvalidFdt)?;
+            rest = &rest[position + end.len()..];
+            continue;
+        }
+
+        let name_length = rest
+            .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+            .ok_or(Error::InvalidFdt)?;
+        let name = &rest[..name_length];
+        let name = name.rsplit(':').next().unwrap_or(name);
+        rest = &rest[name_length..];
+
+        let mut attributes = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if let Some(tail) = rest.strip_prefix("/>").or_else(|| rest.strip_prefix('>')) {
+                rest = tail;
+                break;
+            }
+
+            let (attribute, tail) = rest.split_once('=').ok_or(Error::InvalidFdt)?;
+            let tail = tail.trim_start();
+            let quote = tail.chars().next().filter(|c| *c == '"' || *c == '\'');
+            let quote = quote.ok_or(Error::InvalidFdt)?;
+            let (value, tail) = tail[1..].split_once(quote).ok_or(Error::InvalidFdt)?;
+            attributes.push((attribute.trim(), unescape(value)?));
+            rest = tail;
+        }
+
+        elements.push(Element { name, attributes });
+    }
+
+    Ok(elements)
+}
+
+/// Replace the predefined entities and the character references of an attribute value
+fn unescape(value: &str) -> Result<String, Error> {
+    let mut output = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        output.push_str(&rest[..start]);
+        let (entity, tail) = rest[start + 1..].split_once(';').ok_or(Error::InvalidFdt)?;
+        let c = match entity {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => {
+                let code = match entity.strip_prefix("#x") {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => entity.strip_prefix('#').and_then(|dec| dec.parse().ok()),
+                };
+                code.and_then(char::from_u32).ok_or(Error::InvalidFdt)?
+            }
+        };
+        output.push(c);
+        rest = tail;
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut output = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let word = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (word >> (18 - 6 * i)) & 0x3F;
+                output.push(BASE64_ALPHABET[index as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
+
+fn base64_decode(data: &str) -> Option<Vec<u8>> {
+    let data = data.trim().as_bytes();
+    if !data.len().is_multiple_of(4) {
+        return None;
+    }
+
+    let mut output = Vec::with_capacity(data.len() / 4 * 3);
+    for (n, chunk) in data.chunks(4).enumerate() {
+        let padding = chunk.iter().rev().take_while(|c| **c == b'=').count();
+        if padding > 2 || (padding > 0 && n + 1 != data.len() / 4) {
+            return None;
+        }
+
+        let mut word = 0u32;
+        for c in &chunk[..4 - padding] {
+            let value = BASE64_ALPHABET.iter().position(|a| a == c)?;
+            word = (word << 6) | value as u32;
+        }
+        word <<= 6 * padding;
+        output.extend_from_slice(&word.to_be_bytes()[1..4 - padding]);
+    }
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::error::Error;
+
+    #[test]
+    fn test_base64() {
+        for (data, encoded) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (b"fooba", "Zm9vYmE="),
+            (b"foobar", "Zm9vYmFy"),
+        ] {
+            assert!(super::base64_encode(data) == encoded);
+            assert!(super::base64_decode(encoded).unwrap() == data);
+        }
+        assert!(super::base64_decode("Zg=").is_none());
+        assert!(super::base64_decode("Zg==Zg==").is_none());
+        assert!(super::base64_decode("Z!==").is_none());
+    }
+
+    #[test]
+    fn test_md5() {
+        // RFC 1321 test suite
+        assert!(super::base64_encode(&super::content_md5(b"abc")) == "kAFQmDzST7DWlj99KOF/cg==");
+    }
+
+    #[test]
+    fn test_fdt_round_trip() {
+        let object = vec![7u8; 10000];
+        let oti = crate::ObjectTransmissionInformation::new(10000, 64, 100)
+            .unwrap()
+            .with_sub_blocks(2, 4)
+            .unwrap();
+
+        let mut instance = super::FdtInstance::new(3600);
+        instance.complete = true;
+        instance.files.push(super::FdtFile::new(
+            1,
+            "http://example.com/a&b.bin",
+            &object,
+            oti,
+        ));
+        let mut file = super::FdtFile::new(2, "file\"2\".txt", b"hello", oti);
+        file.content_type = Some("text/plain".into());
+        file.oti = None;
+        instance.files.push(file);
+
+        let xml = instance.to_xml();
+        assert!(xml.contains("Content-Location=\"http://example.com/a&amp;b.bin\""));
+        assert!(super::FdtInstance::from_xml(&xml).unwrap() == instance);
+        assert!(instance.file(2).unwrap().content_length == Some(5));
+        assert!(instance.file(3).is_none());
+
+        assert!(instance.files[0].object_decoder(vec![0u8; 10000]).is_some());
+        assert!(instance.files[0].object_decoder(vec![0u8; 100]).is_none());
+        assert!(instance.files[1].object_decoder(vec![0u8; 5]).is_none());
+    }
+
+    #[test]
+    fn test_fdt_small_object() {
+        crate::tests::init();
+
+        // 2 source symbols
+        let object: Vec<u8> = (0..200).map(|i| i as u8).collect();
+        let encoder = crate::ObjectEncoder::new(&object, 128, 64).unwrap();
+        let mut instance = super::FdtInstance::new(3600);
+        instance.files.push(encoder.fdt_file(1, "small.bin"));
+        let xml = instance.to_xml();
+        assert!(xml.contains("FEC-OTI-Maximum-Source-Block-Length=\"2\""));
+
+        let instance = super::FdtInstance::from_xml(&xml).unwrap();
+        let mut decoder = instance.files[0].object_decoder(vec![0u8; 200]).unwrap();
+        let mut block_encoder = encoder.source_block_encoder(0).unwrap();
+        for esi in 2..6 {
+            decoder.push_encoding_symbol(0, esi, &block_encoder.fountain(esi));
+        }
+        assert!(decoder.finish().unwrap() == object);
+    }
+
+    #[test]
+    fn test_fdt_parse() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- FEC attributes shared by all the files -->
+<fdt:FDT-Instance xmlns:fdt="urn:ietf:params:xml:ns:fdt" Expires='2890842807'
+    FEC-OTI-FEC-Encoding-ID="1" FEC-OTI-Encoding-Symbol-Length="1024" FEC-OTI-Scheme-Specific-Info="AAEBAQ==">
+  <fdt:File TOI="2" Content-Location="file:///a&#x2F;b" Content-Length="1000">
+    <fdt:Group>g</fdt:Group>
+  </fdt:File>
+  <fdt:File TOI="3" Content-Location="c" FEC-OTI-FEC-Encoding-ID="0" Content-Length="5"/>
+</fdt:FDT-Instance>"#;
+
+        let instance = super::FdtInstance::from_xml(xml).unwrap();
+        assert!(instance.expires == 2890842807 && !instance.complete);
+        assert!(instance.files.len() == 2);
+
+        let file = instance.file(2).unwrap();
+        assert!(file.content_location == "file:///a/b");
+        let oti = file.oti.unwrap();
+        assert!(oti.transfer_length == 1000 && oti.symbol_size == 1024);
+        assert!(oti.nb_source_blocks == 1 && oti.nb_sub_blocks == 1 && oti.alignment == 1);
+        // Compact No-Code FEC is not supported
+        assert!(instance.file(3).unwrap().oti.is_none());
+    }
+
+    #[test]
+    fn test_invalid_fdt() {
+        for xml in [
+            "",
+            "<File TOI=\"1\" Content-Location=\"a\"/>",
+            "<FDT-Instance Expires=\"1\"><File Content-Location=\"a\"/></FDT-Instance>",
+            "<FDT-Instance Expires=\"1\"><File TOI=\"1\"/></FDT-Instance>",
+            "<FDT-Instance Expires=\"1\"><File TOI=\"1\" Content-Location=\"a&nbsp;\"/></FDT-Instance>",
+            "<FDT-Instance Expires=\"1\"><File TOI=\"1\" Content-Location=a/></FDT-Instance>",
+            "<FDT-Instance Expires=\"1\"><File TOI=\"1\" Content-Location=\"a\" FEC-OTI-FEC-Encoding-ID=\"1\"/></FDT-Instance>",
+            "<FDT-Instance Expires=\"1\"><File TOI=\"1\" Content-Location=\"a\" Content-MD5=\"AA==\"/></FDT-Instance>",
+            "<FDT-Instance Expires=\"1\"",
+        ] {
+            assert!(super::FdtInstance::from_xml(xml) == Err(Error::InvalidFdt));
+        }
+    }
+}
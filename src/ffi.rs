@@ -0,0 +1,315 @@
+//!
+//! C API
+//!
+//! Opaque handles on [`SourceBlockEncoder`] and [`SourceBlockDecoder`] for C and C++ applications.
+//! The header `include/raptor_code.h` is generated with
+//! ```text
+//! cbindgen --config cbindgen.toml --output include/raptor_code.h
+//! ```
+//!
+//! The static and shared libraries are built by the `raptor-code-ffi` package of the workspace
+//! ```text
+//! cargo build --release --package raptor-code-ffi
+//! ```
+//!
+//! Functions never unwind into C: invalid arguments and panics are reported with a [`RaptorStatus`].
+//!
+
+use crate::{SourceBlockDecoder, SourceBlockEncoder, MAX_SOURCE_SYMBOLS_PER_BLOCK};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Status returned by the functions of the C API
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RaptorStatus {
+    /// Success
+    Ok = 0,
+    /// A pointer argument is NULL
+    NullPointer = -1,
+    /// The output buffer is too small
+    BufferTooSmall = -2,
+    /// Not enough encoding symbols have been received to decode the block
+    NotDecodable = -3,
+    /// Internal error
+    Panic = -4,
+}
+
+/// Opaque handle on a source block encoder
+pub struct RaptorEncoder(SourceBlockEncoder);
+
+/// Opaque handle on a source block decoder
+pub struct RaptorDecoder(SourceBlockDecoder);
+
+fn guard(f: impl FnOnce() -> RaptorStatus) -> RaptorStatus {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(RaptorStatus::Panic)
+}
+
+/// Create a source block encoder
+///
+/// Returns NULL if `data` is NULL, `length` is 0 or `max_source_symbols` is not in 1..=8192.
+/// Blocks of less than 4 source symbols are padded with zero symbols, see [`SourceBlockEncoder::new`].
+/// The encoder must be released with [`raptor_encoder_free`].
+///
+/// # Safety
+///
+/// `data` must point to `length` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn raptor_encoder_new(
+    data: *const u8,
+    length: usize,
+    max_source_symbols: usize,
+) -> *mut RaptorEncoder {
+    if data.is_null()
+        || length == 0
+        || max_source_symbols == 0
+        || max_source_symbols > MAX_SOURCE_SYMBOLS_PER_BLOCK as usize
+    {
+        return core::ptr::null_mut();
+    }
+
+    let data = core::slice::from_raw_parts(data, length);
+    catch_unwind(|| SourceBlockEncoder::new(data, max_source_symbols))
+        .map(|encoder| Box::into_raw(Box::new(RaptorEncoder(encoder))))
+        .unwrap_or(core::ptr::null_mut())
+}
+
+/// Return the number of source symbols of the block, or 0 if `encoder` is NULL
+///
+/// # Safety
+///
+/// `encoder` must be NULL or a handle returned by [`raptor_encoder_new`].
+#[no_mangle]
+pub unsafe extern "C" fn raptor_encoder_nb_source_symbols(encoder: *const RaptorEncoder) -> u32 {
+    match encoder.as_ref() {
+        Some(encoder) => encoder.0.nb_source_symbols(),
+        None => 0,
+    }
+}
+
+/// Generate the encoding symbol `esi` into `buffer`
+///
+/// The length of the encoding symbol is written to `symbol_length`,
+/// including when `buffer` is too small.
+///
+/// # Safety
+///
+/// `encoder` must be a handle returned by [`raptor_encoder_new`],
+/// `buffer` must point to `buffer_length` writable bytes and `symbol_length` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn raptor_encoder_fountain(
+    encoder: *mut RaptorEncoder,
+    esi: u32,
+    buffer: *mut u8,
+    buffer_length: usize,
+    symbol_length: *mut usize,
+) -> RaptorStatus {
+    let (Some(encoder), Some(symbol_length)) = (encoder.as_mut(), symbol_length.as_mut()) else {
+        return RaptorStatus::NullPointer;
+    };
+    if buffer.is_null() {
+        return RaptorStatus::NullPointer;
+    }
+
+    guard(|| {
+        let symbol = encoder.0.fountain(esi);
+        *symbol_length = symbol.len();
+        if symbol.len() > buffer_length {
+            return RaptorStatus::BufferTooSmall;
+        }
+
+        let buffer = core::slice::from_raw_parts_mut(buffer, symbol.len());
+        buffer.copy_from_slice(&symbol);
+        RaptorStatus::Ok
+    })
+}
+
+/// Release an encoder, NULL is ignored
+///
+/// # Safety
+///
+/// `encoder` must be NULL or a handle returned by [`raptor_encoder_new`], and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn raptor_encoder_free(encoder: *mut RaptorEncoder) {
+    if !encoder.is_null() {
+        drop(Box::from_raw(encoder));
+    }
+}
+
+/// Create a decoder for a source block of `nb_source_symbols` source symbols
+///
+/// Returns NULL if `nb_source_symbols` is not in 1..=8192.
+/// Blocks of less than 4 source symbols are padded with zero symbols, see [`SourceBlockDecoder::new`].
+/// The decoder must be released with [`raptor_decoder_free`].
+#[no_mangle]
+pub extern "C" fn raptor_decoder_new(nb_source_symbols: usize) -> *mut RaptorDecoder {
+    if nb_source_symbols == 0 || nb_source_symbols > MAX_SOURCE_SYMBOLS_PER_BLOCK as usize {
+        return core::ptr::null_mut();
+    }
+
+    catch_unwind(|| SourceBlockDecoder::new(nb_source_symbols))
+        .map(|decoder| Box::into_raw(Box::new(RaptorDecoder(decoder))))
+        .unwrap_or(core::ptr::null_mut())
+}
+
+/// Push an encoding symbol to the decoder
+///
+/// # Safety
+///
+/// `decoder` must be a handle returned by [`raptor_decoder_new`]
+/// and `symbol` must point to `length` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn raptor_decoder_push(
+    decoder: *mut RaptorDecoder,
+    symbol: *const u8,
+    length: usize,
+    esi: u32,
+) -> RaptorStatus {
+    let Some(decoder) = decoder.as_mut() else {
+        return RaptorStatus::NullPointer;
+    };
+    if symbol.is_null() {
+        return RaptorStatus::NullPointer;
+    }
+
+    let symbol = core::slice::from_raw_parts(symbol, length);
+    guard(|| {
+        decoder.0.push_encoding_symbol(symbol, esi);
+        RaptorStatus::Ok
+    })
+}
+
+/// Return true when the block can be fully decoded
+///
+/// # Safety
+///
+/// `decoder` must be NULL or a handle returned by [`raptor_decoder_new`].
+#[no_mangle]
+pub unsafe extern "C" fn raptor_decoder_fully_specified(decoder: *const RaptorDecoder) -> bool {
+    match decoder.as_ref() {
+        Some(decoder) => decoder.0.fully_specified(),
+        None => false,
+    }
+}
+
+/// Decode the source block into `buffer`, `length` is the size of the source block in bytes
+///
+/// # Safety
+///
+/// `decoder` must be a handle returned by [`raptor_decoder_new`]
+/// and `buffer` must point to `length` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn raptor_decoder_decode(
+    decoder: *mut RaptorDecoder,
+    buffer: *mut u8,
+    length: usize,
+) -> RaptorStatus {
+    let Some(decoder) = decoder.as_mut() else {
+        return RaptorStatus::NullPointer;
+    };
+    if buffer.is_null() {
+        return RaptorStatus::NullPointer;
+    }
+
+    let buffer = core::slice::from_raw_parts_mut(buffer, length);
+    guard(|| match decoder.0.decode_into(buffer) {
+        true => RaptorStatus::Ok,
+        false => RaptorStatus::NotDecodable,
+    })
+}
+
+/// Release a decoder, NULL is ignored
+///
+/// # Safety
+///
+/// `decoder` must be NULL or a handle returned by [`raptor_decoder_new`], and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn raptor_decoder_free(decoder: *mut RaptorDecoder) {
+    if !decoder.is_null() {
+        drop(Box::from_raw(decoder));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::RaptorStatus;
+
+    #[test]
+    fn test_ffi_invalid_arguments() {
+        unsafe {
+            assert!(super::raptor_encoder_new(core::ptr::null(), 10, 4).is_null());
+            assert!(super::raptor_encoder_new([0u8; 10].as_ptr(), 10, 0).is_null());
+            assert!(super::raptor_decoder_new(0).is_null());
+            assert!(super::raptor_decoder_new(10000).is_null());
+
+            let mut length = 0;
+            let mut buffer = [0u8; 4];
+            let encoder = super::raptor_encoder_new([1u8; 40].as_ptr(), 40, 4);
+            assert!(super::raptor_encoder_nb_source_symbols(encoder) == 4);
+            assert!(
+                super::raptor_encoder_fountain(encoder, 0, buffer.as_mut_ptr(), 4, &mut length)
+                    == RaptorStatus::BufferTooSmall
+            );
+            assert!(length == 10);
+            super::raptor_encoder_free(encoder);
+
+            let decoder = super::raptor_decoder_new(4);
+            assert!(
+                super::raptor_decoder_push(decoder, core::ptr::null(), 10, 0)
+                    == RaptorStatus::NullPointer
+            );
+            assert!(!super::raptor_decoder_fully_specified(decoder));
+            assert!(
+                super::raptor_decoder_decode(decoder, buffer.as_mut_ptr(), 4)
+                    == RaptorStatus::NotDecodable
+            );
+            super::raptor_decoder_free(decoder);
+            super::raptor_decoder_free(core::ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_ffi_small_source_blocks() {
+        // Blocks of 2 and 3 source symbols, decoded from the repair symbols only
+        for (data, max_source_symbols, k) in [
+            (&[7u8][..], 10, 2),
+            (&[7u8; 2][..], 10, 3),
+            (&[7u8; 100][..], 2, 2),
+            (&[7u8; 100][..], 3, 3),
+        ] {
+            unsafe {
+                let encoder =
+                    super::raptor_encoder_new(data.as_ptr(), data.len(), max_source_symbols);
+                assert!(!encoder.is_null());
+                assert!(super::raptor_encoder_nb_source_symbols(encoder) == k);
+
+                let decoder = super::raptor_decoder_new(k as usize);
+                assert!(!decoder.is_null());
+                let mut symbol = [0u8; 100];
+                let mut length = 0;
+                let mut esi = k;
+                while !super::raptor_decoder_fully_specified(decoder) {
+                    let status = super::raptor_encoder_fountain(
+                        encoder,
+                        esi,
+                        symbol.as_mut_ptr(),
+                        symbol.len(),
+                        &mut length,
+                    );
+                    assert!(status == RaptorStatus::Ok);
+                    super::raptor_decoder_push(decoder, symbol.as_ptr(), length, esi);
+                    esi += 1;
+                }
+
+                let mut output = vec![0u8; data.len()];
+                assert!(
+                    super::raptor_decoder_decode(decoder, output.as_mut_ptr(), output.len())
+                        == RaptorStatus::Ok
+                );
+                assert!(output == data);
+                super::raptor_encoder_free(encoder);
+                super::raptor_decoder_free(decoder);
+            }
+        }
+    }
+}
@@ -80,6 +80,46 @@
 //!
 //! ```
 //!
+//! # Example : Object Encoder/Decoder
+//!
+//! Objects larger than a source block are split into several source blocks.
+//! With the `mmap` feature, `ObjectEncoder::open` and `ObjectDecoder::create` work directly on files.
+//!
+//! ```
+//! let object: Vec<u8> = vec![7; 100 * 1024];
+//! let encoder = raptor_code::ObjectEncoder::new(&object, 1024, 32).unwrap();
+//! let oti = *encoder.transmission_information();
+//!
+//! let mut decoder = raptor_code::ObjectDecoder::new(oti, vec![0; object.len()]).unwrap();
+//! for sbn in 0..encoder.nb_source_blocks() {
+//!     let mut block_encoder = encoder.source_block_encoder(sbn).unwrap();
+//!     for esi in 0..block_encoder.nb_source_symbols() {
+//!         let encoding_symbol = block_encoder.fountain(esi);
+//!         decoder.push_encoding_symbol(sbn, esi, &encoding_symbol);
+//!     }
+//! }
+//!
+//! assert!(decoder.fully_decoded());
+//! assert!(decoder.finish().unwrap() == object);
+//! ```
+//!
+//! # no_std
+//!
+//! The crate only requires `alloc` when the default `std` feature is disabled.
+//! The `log` feature, enabled by default, reports discarded encoding symbols with the `log` crate.
+//!
+//! ```toml
+//! raptor-code = { version = "1", default-features = false }
+//! ```
+//!
+//! # Features
+//!
+//! * `manifest`: transfer manifest of an object with its SHA-256 digest, [`ObjectManifest`]
+//! * `fdt`: FLUTE File Delivery Table with the MD5 digest of the objects, [`fdt`]
+//! * `auth`: HMAC-SHA256 authentication of the encoding symbols, [`SymbolAuthenticator`]
+//! * `bytes`: zero-copy encoding symbols with the `bytes` crate
+//! * `mmap`: memory-mapped objects
+//!
 //! # Credit
 //!
 //! RFC 5053 <https://www.rfc-editor.org/rfc/rfc5053.html>  
@@ -91,20 +131,62 @@
 
 #![deny(missing_docs)]
 #![cfg_attr(test, deny(warnings))]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
+pub mod alc;
+#[cfg(feature = "auth")]
+mod auth;
+pub mod ccsds;
+mod checksum;
 mod common;
 mod decoder;
 mod encoder;
 mod encodingsymbols;
+mod error;
+#[cfg(feature = "fdt")]
+pub mod fdt;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "manifest")]
+mod manifest;
+mod object;
 mod partition;
+#[cfg(feature = "python")]
+mod python;
 mod raptor;
+pub mod route;
+mod row;
+pub mod rtp;
 mod sparse_matrix;
+mod static_decoder;
+mod sub_block;
 mod tables;
+#[cfg(feature = "wasm")]
+mod wasm;
 
+#[cfg(feature = "auth")]
+pub use auth::SymbolAuthenticator;
+pub use checksum::Checksum;
 pub use decoder::decode_source_block;
+pub use decoder::DecoderStatistics;
 pub use decoder::SourceBlockDecoder;
 pub use encoder::encode_source_block;
 pub use encoder::SourceBlockEncoder;
+pub use error::Error;
+#[cfg(feature = "manifest")]
+pub use manifest::ObjectManifest;
+pub use object::ObjectDecoder;
+pub use object::ObjectEncoder;
+pub use object::ObjectTransmissionInformation;
+pub use object::MAX_SOURCE_SYMBOLS_PER_BLOCK;
+pub use object::MIN_SOURCE_SYMBOLS_PER_BLOCK;
+pub use static_decoder::nb_coefficient_words;
+pub use static_decoder::nb_intermediate_symbols;
+pub use static_decoder::StaticDecoder;
+pub use sub_block::SubBlockDecoder;
+pub use sub_block::SubBlockEncoder;
 
 #[cfg(test)]
 mod tests {
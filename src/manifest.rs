@@ -0,0 +1,191 @@
+use sha2::{Digest, Sha256};
+
+use crate::error::Error;
+use crate::object::ObjectTransmissionInformation;
+use alloc::vec::Vec;
+
+/// Magic number at the beginning of a serialized manifest
+const MAGIC: &[u8; 4] = b"RPTM";
+
+/// Version of the manifest format
+///
+/// * 1: FEC Object Transmission Information without G, N and Al (49 bytes)
+/// * 2: G added (50 bytes)
+/// * 3: N and Al added (52 bytes)
+const VERSION: u8 = 3;
+
+///
+/// Transfer manifest of an object
+///
+/// The manifest is sent alongside the encoding symbols. It carries the FEC Object Transmission Information,
+/// from which the length of the object and its source blocks layout are derived, and the SHA-256 digest of the object
+/// checked by the receiver once the object is decoded.
+///
+/// Serialized format (network byte order), manifests of the previous versions are still parsed
+/// ```text
+///  0                   1                   2                   3
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |                     Magic Number ("RPTM")                     |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |    Version    |                                               |
+/// +-+-+-+-+-+-+-+-+          Transfer Length (F, 64 bits)         +
+/// |                                                               |
+/// +               +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |               |        Symbol Size (T)        |       Z       |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |       Z       |       G       |       N       |       Al      |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |                                                               |
+/// +                    SHA-256 digest (32 bytes)                  +
+/// |                                                               |
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectManifest {
+    /// FEC Object Transmission Information
+    pub oti: ObjectTransmissionInformation,
+    /// SHA-256 digest of the object
+    pub sha256: [u8; 32],
+}
+
+impl ObjectManifest {
+    /// Size of a serialized manifest of the current version in bytes
+    pub const SIZE: usize = 4 + 1 + 8 + 2 + 2 + 1 + 1 + 1 + 32;
+
+    /// Create the manifest of an object
+    pub fn new(oti: ObjectTransmissionInformation, object: &[u8]) -> Self {
+        ObjectManifest {
+            oti,
+            sha256: Sha256::digest(object).into(),
+        }
+    }
+
+    /// Return true if `object` matches the digest of the manifest
+    pub fn verify(&self, object: &[u8]) -> bool {
+        let sha256: [u8; 32] = Sha256::digest(object).into();
+        sha256 == self.sha256
+    }
+
+    /// Serialize the manifest
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut output = Vec::with_capacity(Self::SIZE);
+        output.extend_from_slice(MAGIC);
+        output.push(VERSION);
+        output.extend_from_slice(&self.oti.transfer_length.to_be_bytes());
+        output.extend_from_slice(&self.oti.symbol_size.to_be_bytes());
+        output.extend_from_slice(&self.oti.nb_source_blocks.to_be_bytes());
+        output.push(self.oti.symbols_per_packet);
+        output.push(self.oti.nb_sub_blocks);
+        output.push(self.oti.alignment);
+        output.extend_from_slice(&self.sha256);
+        output
+    }
+
+    /// Parse a serialized manifest
+    pub fn from_bytes(data: &[u8]) -> Result<Self, Error> {
+        if data.len() < 5 || &data[0..4] != MAGIC {
+            return Err(Error::InvalidManifest);
+        }
+
+        // Number of parameters after Z, the missing ones are 1
+        let nb_parameters = match data[4] {
+            1 => 0,
+            2 => 1,
+            VERSION => 3,
+            _ => return Err(Error::InvalidManifest),
+        };
+        if data.len() != Self::SIZE - 3 + nb_parameters {
+            return Err(Error::InvalidManifest);
+        }
+
+        let transfer_length = u64::from_be_bytes(data[5..13].try_into().unwrap());
+        let symbol_size = u16::from_be_bytes(data[13..15].try_into().unwrap());
+        let nb_source_blocks = u16::from_be_bytes(data[15..17].try_into().unwrap());
+        let (parameters, sha256) = data[17..].split_at(nb_parameters);
+        let oti = ObjectTransmissionInformation {
+            transfer_length,
+            symbol_size,
+            nb_source_blocks,
+            symbols_per_packet: parameters.first().copied().unwrap_or(1),
+            nb_sub_blocks: parameters.get(1).copied().unwrap_or(1),
+            alignment: parameters.get(2).copied().unwrap_or(1),
+        };
+        if !oti.is_valid() {
+            return Err(Error::InvalidManifest);
+        }
+
+        Ok(ObjectManifest {
+            oti,
+            sha256: sha256.try_into().unwrap(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+
+    #[test]
+    fn test_manifest_serialization() {
+        let object = vec![3u8; 5000];
+        let oti = super::ObjectTransmissionInformation::new(5000, 100, 20).unwrap();
+        let manifest = super::ObjectManifest::new(oti, &object);
+        assert!(manifest.verify(&object));
+        assert!(!manifest.verify(&object[1..]));
+
+        let data = manifest.to_bytes();
+        assert!(data.len() == super::ObjectManifest::SIZE);
+        assert!(super::ObjectManifest::from_bytes(&data).unwrap() == manifest);
+
+        assert!(super::ObjectManifest::from_bytes(&data[1..]) == Err(Error::InvalidManifest));
+        let mut invalid = data.clone();
+        invalid[4] = 4;
+        assert!(super::ObjectManifest::from_bytes(&invalid) == Err(Error::InvalidManifest));
+        // Layout of the version 2 with the version 3
+        assert!(super::ObjectManifest::from_bytes(&data[..51]) == Err(Error::InvalidManifest));
+        // Symbol size of 0
+        let mut invalid = data.clone();
+        invalid[13..15].copy_from_slice(&[0, 0]);
+        assert!(super::ObjectManifest::from_bytes(&invalid) == Err(Error::InvalidManifest));
+        // No encoding symbol per packet
+        let mut invalid = data.clone();
+        invalid[17] = 0;
+        assert!(super::ObjectManifest::from_bytes(&invalid) == Err(Error::InvalidManifest));
+        // More sub-blocks than sub-symbols
+        let mut invalid = data.clone();
+        invalid[18] = 101;
+        assert!(super::ObjectManifest::from_bytes(&invalid) == Err(Error::InvalidManifest));
+    }
+
+    #[test]
+    fn test_manifest_previous_versions() {
+        let object = vec![5u8; 5000];
+        let oti = super::ObjectTransmissionInformation::new(5000, 100, 20).unwrap();
+        let sha256 = super::ObjectManifest::new(oti, &object).sha256;
+
+        // Version 1: F, T, Z
+        let mut version_1 = b"RPTM\x01".to_vec();
+        version_1.extend_from_slice(&5000u64.to_be_bytes());
+        version_1.extend_from_slice(&100u16.to_be_bytes());
+        version_1.extend_from_slice(&3u16.to_be_bytes());
+        version_1.extend_from_slice(&sha256);
+        assert!(version_1.len() == 49);
+        let manifest = super::ObjectManifest::from_bytes(&version_1).unwrap();
+        assert!(manifest.oti == oti);
+        assert!(manifest.verify(&object));
+
+        // Version 2: F, T, Z, G
+        let mut version_2 = b"RPTM\x02".to_vec();
+        version_2.extend_from_slice(&version_1[5..17]);
+        version_2.push(4);
+        version_2.extend_from_slice(&sha256);
+        let manifest = super::ObjectManifest::from_bytes(&version_2).unwrap();
+        assert!(manifest.oti == oti.with_symbols_per_packet(4).unwrap());
+        assert!(manifest.sha256 == sha256);
+
+        // Layout of the version 1 with the version 2
+        version_1[4] = 2;
+        assert!(super::ObjectManifest::from_bytes(&version_1) == Err(Error::InvalidManifest));
+    }
+}
@@ -0,0 +1,987 @@
+#[cfg(feature = "auth")]
+use crate::auth::SymbolAuthenticator;
+use crate::checksum::Checksum;
+use crate::error::Error;
+#[cfg(feature = "fdt")]
+use crate::fdt::FdtFile;
+#[cfg(feature = "manifest")]
+use crate::manifest::ObjectManifest;
+use crate::partition::Partition;
+use crate::{SourceBlockEncoder, SubBlockDecoder, SubBlockEncoder};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Maximum number of source symbols in a source block (K_MAX)
+/// RFC 5053 section 5.1.2
+pub const MAX_SOURCE_SYMBOLS_PER_BLOCK: u32 = 8192;
+
+/// Minimum number of source symbols of the Raptor code
+/// The systematic indices of RFC 5053 section 5.7 are defined from K = 4, the source block
+/// encoder and decoder pad smaller source blocks with zero symbols that are never sent.
+pub const MIN_SOURCE_SYMBOLS_PER_BLOCK: u32 = 4;
+
+/// Maximum number of source blocks of an object (Z is encoded on 16 bits)
+const MAX_SOURCE_BLOCKS: u64 = u16::MAX as u64;
+
+///
+/// FEC Object Transmission Information
+///
+/// Parameters shared by the sender and the receivers of an object, as specified in RFC 5053 section 3.2.
+/// An object of `transfer_length` bytes is split into `nb_source_blocks` source blocks of symbols of `symbol_size` bytes.
+/// Each packet carries `symbols_per_packet` consecutive encoding symbols of a source block.
+/// Source blocks are split into `nb_sub_blocks` sub-blocks of sub-symbols aligned on `alignment` bytes.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObjectTransmissionInformation {
+    /// Length of the object in bytes (F)
+    pub transfer_length: u64,
+    /// Size of an encoding symbol in bytes (T)
+    pub symbol_size: u16,
+    /// Number of source blocks (Z)
+    pub nb_source_blocks: u16,
+    /// Number of encoding symbols per packet (G)
+    pub symbols_per_packet: u8,
+    /// Number of sub-blocks of a source block (N)
+    pub nb_sub_blocks: u8,
+    /// Alignment of the sub-symbols in bytes (Al)
+    pub alignment: u8,
+}
+
+/// Location of a source block inside an object
+pub(crate) struct SourceBlock {
+    pub offset: usize,
+    pub length: usize,
+    pub nb_source_symbols: u32,
+    /// Length of the block padded with zeros to K symbols of T bytes, when it is shorter
+    pub padded_length: Option<usize>,
+}
+
+impl ObjectTransmissionInformation {
+    /// Compute the transmission information of an object
+    ///
+    /// # Parameters
+    ///
+    /// * `transfer_length`: Length of the object in bytes.
+    /// * `symbol_size`: Size of an encoding symbol in bytes (T).
+    /// * `max_source_symbols_per_block`: Max number of source symbols inside a source block,
+    ///   between 1 and [`MAX_SOURCE_SYMBOLS_PER_BLOCK`].
+    ///
+    /// # Returns
+    ///
+    /// * `None` if the object cannot be split into at most 65535 source blocks with these parameters
+    /// * `Some(ObjectTransmissionInformation)` otherwise
+    pub fn new(
+        transfer_length: u64,
+        symbol_size: u16,
+        max_source_symbols_per_block: u32,
+    ) -> Option<Self> {
+        if symbol_size == 0
+            || !(1..=MAX_SOURCE_SYMBOLS_PER_BLOCK).contains(&max_source_symbols_per_block)
+        {
+            return None;
+        }
+
+        let kt = transfer_length.div_ceil(symbol_size as u64);
+        let z = kt.div_ceil(max_source_symbols_per_block as u64);
+        if z > MAX_SOURCE_BLOCKS {
+            return None;
+        }
+
+        Some(ObjectTransmissionInformation {
+            transfer_length,
+            symbol_size,
+            nb_source_blocks: z as u16,
+            symbols_per_packet: 1,
+            nb_sub_blocks: 1,
+            alignment: 1,
+        })
+    }
+
+    /// Set the number of encoding symbols per packet (G), 1 by default
+    ///
+    /// Grouping several symbols into a packet keeps the symbol size small relative to the MTU.
+    ///
+    /// # Returns
+    ///
+    /// * `None` if `symbols_per_packet` is 0
+    /// * `Some(ObjectTransmissionInformation)` otherwise
+    pub fn with_symbols_per_packet(mut self, symbols_per_packet: u8) -> Option<Self> {
+        if symbols_per_packet == 0 {
+            return None;
+        }
+        self.symbols_per_packet = symbols_per_packet;
+        Some(self)
+    }
+
+    /// Split the source blocks into `nb_sub_blocks` sub-blocks (N), 1 by default
+    ///
+    /// Every encoding symbol is split into N sub-symbols of a multiple of `alignment` bytes,
+    /// see [`crate::SubBlockEncoder`].
+    ///
+    /// # Returns
+    ///
+    /// * `None` if the symbol size is not a multiple of `alignment` or cannot be split into `nb_sub_blocks` sub-symbols
+    /// * `Some(ObjectTransmissionInformation)` otherwise
+    pub fn with_sub_blocks(mut self, nb_sub_blocks: u8, alignment: u8) -> Option<Self> {
+        self.nb_sub_blocks = nb_sub_blocks;
+        self.alignment = alignment;
+        self.is_valid().then_some(self)
+    }
+
+    /// Split the source blocks into the smallest number of sub-blocks fitting in `working_memory` bytes
+    ///
+    /// The number of sub-blocks is derived as recommended by RFC 5053 section 4.2:
+    /// N = min(ceil(ceil(Kt/Z) * T / W), T / Al)
+    ///
+    /// The receivers decode one sub-block at a time, see [`crate::SubBlockDecoder`].
+    ///
+    /// # Parameters
+    ///
+    /// * `alignment`: Alignment of the sub-symbols in bytes (Al).
+    /// * `working_memory`: Max size of a sub-block in bytes (W).
+    ///
+    /// # Returns
+    ///
+    /// * `None` if the parameters are inconsistent or more than 255 sub-blocks are needed
+    /// * `Some(ObjectTransmissionInformation)` otherwise
+    pub fn with_working_memory(self, alignment: u8, working_memory: u64) -> Option<Self> {
+        if alignment == 0 || working_memory == 0 {
+            return None;
+        }
+
+        let kt = self.nb_source_symbols();
+        let z = (self.nb_source_blocks as u64).max(1);
+        let block_size = kt.div_ceil(z) * self.symbol_size as u64;
+        let nb_sub_blocks = block_size
+            .div_ceil(working_memory)
+            .min(self.symbol_size as u64 / alignment as u64)
+            .max(1);
+        self.with_sub_blocks(u8::try_from(nb_sub_blocks).ok()?, alignment)
+    }
+
+    /// Check that the parameters describe a valid source block partitioning
+    ///
+    /// Transmission information received from the network must be checked before use.
+    pub fn is_valid(&self) -> bool {
+        if self.symbol_size == 0
+            || self.symbols_per_packet == 0
+            || self.alignment == 0
+            || self.nb_sub_blocks == 0
+            || !self.symbol_size.is_multiple_of(self.alignment as u16)
+            || self.nb_sub_blocks as u16 > self.symbol_size / self.alignment as u16
+        {
+            return false;
+        }
+
+        let kt = self.nb_source_symbols();
+        let z = self.nb_source_blocks as u64;
+        if kt == 0 {
+            return z == 0;
+        }
+
+        z != 0 && z <= kt && kt.div_ceil(z) <= MAX_SOURCE_SYMBOLS_PER_BLOCK as u64
+    }
+
+    /// Return the total number of source symbols of the object (Kt)
+    pub fn nb_source_symbols(&self) -> u64 {
+        self.transfer_length.div_ceil(self.symbol_size as u64)
+    }
+
+    /// Return the number of source symbols (k) inside the source block `sbn`
+    pub fn nb_source_symbols_in_block(&self, sbn: u32) -> Option<u32> {
+        self.source_block(sbn).map(|block| block.nb_source_symbols)
+    }
+
+    /// Return the length in bytes of the source block `sbn`
+    pub fn source_block_length(&self, sbn: u32) -> Option<usize> {
+        self.source_block(sbn).map(|block| block.length)
+    }
+
+    /// Source block partitioning, RFC 5053 section 5.3.1.2
+    pub(crate) fn source_block(&self, sbn: u32) -> Option<SourceBlock> {
+        if sbn >= self.nb_source_blocks as u32 {
+            return None;
+        }
+
+        let sbn = sbn as usize;
+        let partition = Partition::new(
+            self.nb_source_symbols() as usize,
+            self.nb_source_blocks as usize,
+        );
+        let (first_symbol, nb_source_symbols) = if sbn < partition.nb_long {
+            (sbn * partition.long_size, partition.long_size)
+        } else {
+            (
+                partition.nb_long * partition.long_size
+                    + (sbn - partition.nb_long) * partition.small_size,
+                partition.small_size,
+            )
+        };
+
+        let offset = first_symbol * self.symbol_size as usize;
+        let length = (nb_source_symbols * self.symbol_size as usize)
+            .min(self.transfer_length as usize - offset);
+
+        // Every source symbol is T bytes long, the last symbol of the object is padded with zeros
+        let padded_length = nb_source_symbols * self.symbol_size as usize;
+        Some(SourceBlock {
+            offset,
+            length,
+            nb_source_symbols: nb_source_symbols as u32,
+            padded_length: (length < padded_length).then_some(padded_length),
+        })
+    }
+
+    /// Return the SBN and ESI of the source symbol `index` of the object
+    pub(crate) fn symbol_position(&self, index: u64) -> Option<(u32, u32)> {
+        if index >= self.nb_source_symbols() {
+            return None;
+        }
+
+        let index = index as usize;
+        let partition = Partition::new(
+            self.nb_source_symbols() as usize,
+            self.nb_source_blocks as usize,
+        );
+        let nb_long_symbols = partition.nb_long * partition.long_size;
+        let (sbn, esi) = if index < nb_long_symbols {
+            (index / partition.long_size, index % partition.long_size)
+        } else {
+            let index = index - nb_long_symbols;
+            (
+                partition.nb_long + index / partition.small_size,
+                index % partition.small_size,
+            )
+        };
+        Some((sbn as u32, esi as u32))
+    }
+}
+
+/// A struct that represents an encoder of an object split into several source blocks.
+///
+/// The object is only borrowed, source blocks are encoded one by one when requested so the memory used
+/// by the encoder is bounded to the size of a source block.
+pub struct ObjectEncoder<D> {
+    data: D,
+    oti: ObjectTransmissionInformation,
+    checksum: Option<Checksum>,
+    #[cfg(feature = "auth")]
+    authenticator: Option<SymbolAuthenticator>,
+}
+
+impl<D: AsRef<[u8]>> ObjectEncoder<D> {
+    /// Create an object encoder
+    ///
+    /// # Parameters
+    ///
+    /// * `data`: The object. Any buffer that can be viewed as a slice of bytes (`Vec<u8>`, `&[u8]`, memory-mapped file...)
+    /// * `symbol_size`: Size of an encoding symbol in bytes (T).
+    /// * `max_source_symbols_per_block`: Max number of source symbols inside a source block.
+    ///
+    /// # Returns
+    ///
+    /// * `None` if the object cannot be split with these parameters
+    /// * `Some(ObjectEncoder)` otherwise
+    pub fn new(data: D, symbol_size: u16, max_source_symbols_per_block: u32) -> Option<Self> {
+        let oti = ObjectTransmissionInformation::new(
+            data.as_ref().len() as u64,
+            symbol_size,
+            max_source_symbols_per_block,
+        )?;
+        Some(ObjectEncoder {
+            data,
+            oti,
+            checksum: None,
+            #[cfg(feature = "auth")]
+            authenticator: None,
+        })
+    }
+
+    /// Append a checksum to every encoding symbol
+    ///
+    /// See [`SourceBlockEncoder::with_checksum`]
+    pub fn with_checksum(mut self, checksum: Checksum) -> Self {
+        self.checksum = Some(checksum);
+        self
+    }
+
+    /// Append an authentication tag to every encoding symbol
+    ///
+    /// The tags of each source block are bound to its source block number.
+    /// Bind the authenticator to the object with [`SymbolAuthenticator::for_transfer`] when the key is shared by several objects.
+    /// See [`SourceBlockEncoder::with_authenticator`]
+    #[cfg(feature = "auth")]
+    pub fn with_authenticator(mut self, authenticator: SymbolAuthenticator) -> Self {
+        self.authenticator = Some(authenticator);
+        self
+    }
+
+    /// Send `symbols_per_packet` encoding symbols per packet (G), 1 by default
+    ///
+    /// Packets are generated with [`SourceBlockEncoder::fountain_group`], the ESI of consecutive packets
+    /// increases by `symbols_per_packet`.
+    ///
+    /// # Returns
+    ///
+    /// * `None` if `symbols_per_packet` is 0
+    /// * `Some(ObjectEncoder)` otherwise
+    pub fn with_symbols_per_packet(mut self, symbols_per_packet: u8) -> Option<Self> {
+        self.oti = self.oti.with_symbols_per_packet(symbols_per_packet)?;
+        Some(self)
+    }
+
+    /// Split the source blocks into `nb_sub_blocks` sub-blocks (N)
+    ///
+    /// Source blocks must be encoded with [`ObjectEncoder::sub_block_encoder`].
+    /// See [`ObjectTransmissionInformation::with_sub_blocks`]
+    pub fn with_sub_blocks(mut self, nb_sub_blocks: u8, alignment: u8) -> Option<Self> {
+        self.oti = self.oti.with_sub_blocks(nb_sub_blocks, alignment)?;
+        Some(self)
+    }
+
+    /// Split the source blocks into sub-blocks of at most `working_memory` bytes
+    ///
+    /// Source blocks must be encoded with [`ObjectEncoder::sub_block_encoder`].
+    /// See [`ObjectTransmissionInformation::with_working_memory`]
+    pub fn with_working_memory(mut self, alignment: u8, working_memory: u64) -> Option<Self> {
+        self.oti = self.oti.with_working_memory(alignment, working_memory)?;
+        Some(self)
+    }
+
+    /// Return the FEC Object Transmission Information to send to the receivers
+    pub fn transmission_information(&self) -> &ObjectTransmissionInformation {
+        &self.oti
+    }
+
+    /// Return the number of source blocks (Z) of the object
+    pub fn nb_source_blocks(&self) -> u32 {
+        self.oti.nb_source_blocks as u32
+    }
+
+    /// Create the transfer manifest of the object
+    ///
+    /// The SHA-256 digest of the whole object is computed.
+    #[cfg(feature = "manifest")]
+    pub fn manifest(&self) -> ObjectManifest {
+        ObjectManifest::new(self.oti, self.data.as_ref())
+    }
+
+    /// Create the File Delivery Table entry of the object
+    ///
+    /// The MD5 digest of the whole object is computed.
+    ///
+    /// # Parameters
+    ///
+    /// * `toi`: Transport Object Identifier of the object.
+    /// * `content_location`: URI of the object.
+    #[cfg(feature = "fdt")]
+    pub fn fdt_file(&self, toi: u64, content_location: &str) -> FdtFile {
+        FdtFile::new(toi, content_location, self.data.as_ref(), self.oti)
+    }
+
+    /// Create the encoder of the source block `sbn`
+    ///
+    /// # Returns
+    ///
+    /// * `None` if `sbn` is not a source block of the object or the source blocks are split into sub-blocks
+    /// * `Some(SourceBlockEncoder)` otherwise
+    pub fn source_block_encoder(&self, sbn: u32) -> Option<SourceBlockEncoder> {
+        if self.oti.nb_sub_blocks != 1 {
+            return None;
+        }
+
+        let block = self.oti.source_block(sbn)?;
+        let data = self.source_block_data(&block);
+        let mut encoder = SourceBlockEncoder::new(&data, block.nb_source_symbols as usize);
+        #[cfg(feature = "auth")]
+        if let Some(authenticator) = &self.authenticator {
+            encoder = encoder.with_authenticator(authenticator.for_source_block(sbn));
+        }
+        if let Some(checksum) = self.checksum {
+            encoder = encoder.with_checksum(checksum);
+        }
+        Some(encoder)
+    }
+
+    /// Create the encoder of the source block `sbn`, split into the sub-blocks of the transmission information
+    ///
+    /// Only one sub-block is held in memory at a time while the encoder is created.
+    ///
+    /// # Returns
+    ///
+    /// * `None` if `sbn` is not a source block of the object
+    /// * `Some(SubBlockEncoder)` otherwise
+    pub fn sub_block_encoder(&self, sbn: u32) -> Option<SubBlockEncoder> {
+        let block = self.oti.source_block(sbn)?;
+        let data = self.source_block_data(&block);
+        let mut encoder = SubBlockEncoder::new(
+            &data,
+            self.oti.symbol_size as usize,
+            self.oti.nb_sub_blocks as usize,
+            self.oti.alignment as usize,
+        );
+        #[cfg(feature = "auth")]
+        if let Some(authenticator) = &self.authenticator {
+            encoder = encoder.with_authenticator(authenticator.for_source_block(sbn));
+        }
+        if let Some(checksum) = self.checksum {
+            encoder = encoder.with_checksum(checksum);
+        }
+        Some(encoder)
+    }
+
+    /// Return the data of a source block, padded with zeros to K symbols of T bytes
+    fn source_block_data(&self, block: &SourceBlock) -> Cow<'_, [u8]> {
+        let data = &self.data.as_ref()[block.offset..block.offset + block.length];
+        match block.padded_length {
+            Some(padded_length) => {
+                let mut padded = vec![0u8; padded_length];
+                padded[..data.len()].copy_from_slice(data);
+                Cow::Owned(padded)
+            }
+            None => Cow::Borrowed(data),
+        }
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl ObjectEncoder<memmap2::Mmap> {
+    /// Create an object encoder reading a memory-mapped file
+    ///
+    /// The file is not loaded in memory, pages are read by the operating system when a source block is encoded.
+    /// The file must not be modified while the encoder is in use.
+    ///
+    /// # Parameters
+    ///
+    /// * `path`: Path of the file to encode.
+    /// * `symbol_size`: Size of an encoding symbol in bytes (T).
+    /// * `max_source_symbols_per_block`: Max number of source symbols inside a source block.
+    pub fn open<P: AsRef<std::path::Path>>(
+        path: P,
+        symbol_size: u16,
+        max_source_symbols_per_block: u32,
+    ) -> std::io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        // Safety: the file is mapped read-only and must not be modified while it is mapped
+        let data = unsafe { memmap2::Mmap::map(&file)? };
+        ObjectEncoder::new(data, symbol_size, max_source_symbols_per_block).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid object transmission information",
+            )
+        })
+    }
+}
+
+enum SourceBlockState {
+    Pending,
+    Decoding(Box<SubBlockDecoder>),
+    Decoded,
+}
+
+/// A struct that represents a decoder of an object split into several source blocks.
+///
+/// Each source block is written into the output buffer as soon as it is decoded, then its decoder is released.
+/// Only the source blocks currently being received are kept in memory.
+pub struct ObjectDecoder<W> {
+    output: W,
+    oti: ObjectTransmissionInformation,
+    blocks: Vec<SourceBlockState>,
+    nb_decoded: u32,
+    #[cfg(feature = "manifest")]
+    sha256: Option<[u8; 32]>,
+    #[cfg(feature = "fdt")]
+    md5: Option<[u8; 16]>,
+    checksum: Option<Checksum>,
+    #[cfg(feature = "auth")]
+    authenticator: Option<SymbolAuthenticator>,
+}
+
+impl<W: AsMut<[u8]>> ObjectDecoder<W> {
+    /// Create an object decoder
+    ///
+    /// # Parameters
+    ///
+    /// * `oti`: FEC Object Transmission Information received from the sender.
+    /// * `output`: Buffer receiving the decoded object, its length must be equal to the transfer length.
+    ///
+    /// # Returns
+    ///
+    /// * `None` if `oti` is invalid or the length of `output` does not match the transfer length
+    /// * `Some(ObjectDecoder)` otherwise
+    pub fn new(oti: ObjectTransmissionInformation, mut output: W) -> Option<Self> {
+        if !oti.is_valid() || output.as_mut().len() as u64 != oti.transfer_length {
+            return None;
+        }
+
+        let blocks = (0..oti.nb_source_blocks)
+            .map(|_| SourceBlockState::Pending)
+            .collect();
+
+        Some(ObjectDecoder {
+            output,
+            oti,
+            blocks,
+            nb_decoded: 0,
+            #[cfg(feature = "manifest")]
+            sha256: None,
+            #[cfg(feature = "fdt")]
+            md5: None,
+            checksum: None,
+            #[cfg(feature = "auth")]
+            authenticator: None,
+        })
+    }
+
+    /// Create an object decoder from a transfer manifest
+    ///
+    /// The digest of the decoded object is verified by [`ObjectDecoder::finish`].
+    ///
+    /// # Parameters
+    ///
+    /// * `manifest`: Transfer manifest received from the sender.
+    /// * `output`: Buffer receiving the decoded object, its length must be equal to the transfer length.
+    #[cfg(feature = "manifest")]
+    pub fn from_manifest(manifest: &ObjectManifest, output: W) -> Option<Self> {
+        let mut decoder = ObjectDecoder::new(manifest.oti, output)?;
+        decoder.sha256 = Some(manifest.sha256);
+        Some(decoder)
+    }
+
+    /// Verify the MD5 digest of the decoded object in [`ObjectDecoder::finish`]
+    ///
+    /// The digest is the `Content-MD5` attribute of the File Delivery Table, see [`FdtFile::object_decoder`].
+    #[cfg(feature = "fdt")]
+    pub fn with_content_md5(mut self, md5: [u8; 16]) -> Self {
+        self.md5 = Some(md5);
+        self
+    }
+
+    /// Verify the checksum appended to every encoding symbol
+    ///
+    /// See [`crate::SourceBlockDecoder::with_checksum`]
+    pub fn with_checksum(mut self, checksum: Checksum) -> Self {
+        self.checksum = Some(checksum);
+        self
+    }
+
+    /// Verify the authentication tag appended to every encoding symbol
+    ///
+    /// See [`crate::SourceBlockDecoder::with_authenticator`]
+    #[cfg(feature = "auth")]
+    pub fn with_authenticator(mut self, authenticator: SymbolAuthenticator) -> Self {
+        self.authenticator = Some(authenticator);
+        self
+    }
+
+    /// Push a packet of encoding symbols to the decoder
+    ///
+    /// The packet carries `symbols_per_packet` (G) consecutive encoding symbols of the source block,
+    /// see [`crate::SourceBlockDecoder::push_encoding_symbol_group`].
+    ///
+    /// # Arguments
+    ///
+    /// * `sbn` - Source block number (SBN)
+    /// * `esi` - Encoding symbol identifier (ESI) of the first encoding symbol of the packet
+    /// * `encoding_symbol` - A slice of u8 numbers representing the packet data
+    pub fn push_encoding_symbol(&mut self, sbn: u32, esi: u32, encoding_symbol: &[u8]) {
+        let block = match self.oti.source_block(sbn) {
+            Some(block) => block,
+            None => {
+                #[cfg(feature = "log")]
+                log::warn!("Receive encoding symbol of unknown source block {}", sbn);
+                return;
+            }
+        };
+
+        let state = &mut self.blocks[sbn as usize];
+        if let SourceBlockState::Pending = state {
+            let mut decoder = SubBlockDecoder::new(
+                block.nb_source_symbols as usize,
+                self.oti.symbol_size as usize,
+                self.oti.nb_sub_blocks as usize,
+                self.oti.alignment as usize,
+            );
+            #[cfg(feature = "auth")]
+            if let Some(authenticator) = &self.authenticator {
+                decoder = decoder.with_authenticator(authenticator.for_source_block(sbn));
+            }
+            if let Some(checksum) = self.checksum {
+                decoder = decoder.with_checksum(checksum);
+            }
+            *state = SourceBlockState::Decoding(Box::new(decoder));
+        }
+
+        let decoder = match state {
+            SourceBlockState::Decoding(decoder) => decoder,
+            _ => return,
+        };
+
+        decoder.push_encoding_symbol_group(
+            encoding_symbol,
+            esi,
+            self.oti.symbols_per_packet as u32,
+        );
+        if !decoder.fully_specified() {
+            return;
+        }
+
+        let source_block = &mut self.output.as_mut()[block.offset..block.offset + block.length];
+        let decoded = match block.padded_length {
+            Some(padded_length) => {
+                let mut padded = vec![0u8; padded_length];
+                let decoded = decoder.decode_into(&mut padded);
+                source_block.copy_from_slice(&padded[..block.length]);
+                decoded
+            }
+            None => decoder.decode_into(source_block),
+        };
+        if decoded {
+            *state = SourceBlockState::Decoded;
+            self.nb_decoded += 1;
+        }
+    }
+
+    /// Return true when the source block `sbn` is decoded and written to the output
+    pub fn is_block_decoded(&self, sbn: u32) -> bool {
+        matches!(
+            self.blocks.get(sbn as usize),
+            Some(SourceBlockState::Decoded)
+        )
+    }
+
+    /// Return true when all the source blocks of the object are decoded
+    pub fn fully_decoded(&self) -> bool {
+        self.nb_decoded == self.oti.nb_source_blocks as u32
+    }
+
+    /// Return the FEC Object Transmission Information of the object
+    pub fn transmission_information(&self) -> &ObjectTransmissionInformation {
+        &self.oti
+    }
+
+    /// Consume the decoder and return the decoded object
+    ///
+    /// When the decoder is created from a manifest or configured with a content MD5, the digest of the decoded object is verified.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(Error::Incomplete)` if some source blocks are not decoded
+    /// * `Err(Error::DigestMismatch)` if the decoded object does not match the digest of the manifest or the content MD5
+    /// * `Ok(W)` the output buffer containing the decoded object
+    #[cfg_attr(not(any(feature = "manifest", feature = "fdt")), allow(unused_mut))]
+    pub fn finish(mut self) -> Result<W, Error> {
+        if !self.fully_decoded() {
+            return Err(Error::Incomplete);
+        }
+
+        #[cfg(feature = "manifest")]
+        if let Some(sha256) = &self.sha256 {
+            let manifest = ObjectManifest {
+                oti: self.oti,
+                sha256: *sha256,
+            };
+            if !manifest.verify(self.output.as_mut()) {
+                return Err(Error::DigestMismatch);
+            }
+        }
+
+        #[cfg(feature = "fdt")]
+        if let Some(md5) = &self.md5 {
+            if crate::fdt::content_md5(self.output.as_mut()) != *md5 {
+                return Err(Error::DigestMismatch);
+            }
+        }
+
+        Ok(self.output)
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl ObjectDecoder<memmap2::MmapMut> {
+    /// Create an object decoder writing the decoded source blocks into a memory-mapped file
+    ///
+    /// The file is created (or truncated) and resized to the transfer length of the object.
+    /// Call `flush()` on the output returned by [`ObjectDecoder::finish`] to make sure the data is written to disk.
+    ///
+    /// # Parameters
+    ///
+    /// * `path`: Path of the output file.
+    /// * `oti`: FEC Object Transmission Information received from the sender.
+    pub fn create<P: AsRef<std::path::Path>>(
+        path: P,
+        oti: ObjectTransmissionInformation,
+    ) -> std::io::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.set_len(oti.transfer_length)?;
+        // Safety: the file has just been created by the decoder and is only modified through the mapping
+        let output = unsafe { memmap2::MmapMut::map_mut(&file)? };
+        ObjectDecoder::new(oti, output).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "output file does not match the transfer length",
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use rand::RngCore;
+
+    fn encode_decode(length: usize, symbol_size: u16, max_source_symbols: u32, nb_repair: u32) {
+        crate::tests::init();
+
+        let mut data = vec![0u8; length];
+        rand::thread_rng().fill_bytes(&mut data);
+
+        let encoder = super::ObjectEncoder::new(&data, symbol_size, max_source_symbols).unwrap();
+        let oti = *encoder.transmission_information();
+        let mut decoder = super::ObjectDecoder::new(oti, vec![0u8; length]).unwrap();
+
+        for sbn in 0..encoder.nb_source_blocks() {
+            let mut block_encoder = encoder.source_block_encoder(sbn).unwrap();
+            let n = block_encoder.nb_source_symbols() + nb_repair;
+            // Simulate the loss of the first symbol of each block
+            for esi in 1..n {
+                let encoding_symbol = block_encoder.fountain(esi);
+                decoder.push_encoding_symbol(sbn, esi, &encoding_symbol);
+            }
+            assert!(decoder.is_block_decoded(sbn));
+        }
+
+        assert!(decoder.fully_decoded());
+        assert!(decoder.finish().unwrap() == data);
+    }
+
+    #[test]
+    fn test_source_block_partition() {
+        let oti = super::ObjectTransmissionInformation::new(10000, 10, 300).unwrap();
+        assert!(oti.nb_source_symbols() == 1000);
+        assert!(oti.nb_source_blocks == 4);
+
+        let mut offset = 0;
+        for sbn in 0..4 {
+            let block = oti.source_block(sbn).unwrap();
+            assert!(block.offset == offset);
+            assert!(block.nb_source_symbols == 250);
+            offset += block.length;
+        }
+        assert!(offset == 10000);
+        assert!(oti.source_block(4).is_none());
+
+        // 3 blocks of 4 symbols and 2 blocks of 3 symbols
+        let oti = super::ObjectTransmissionInformation::new(180, 10, 4).unwrap();
+        let mut index = 0;
+        for (sbn, k) in [4, 4, 4, 3, 3].into_iter().enumerate() {
+            let sbn = sbn as u32;
+            assert!(oti.nb_source_symbols_in_block(sbn) == Some(k));
+            for esi in 0..k {
+                assert!(oti.symbol_position(index) == Some((sbn, esi)));
+                index += 1;
+            }
+        }
+        assert!(index == 18);
+        assert!(oti.symbol_position(18).is_none());
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        assert!(super::ObjectTransmissionInformation::new(1000, 0, 10).is_none());
+        assert!(super::ObjectTransmissionInformation::new(1000, 10, 0).is_none());
+        assert!(super::ObjectTransmissionInformation::new(1000, 10, 10000).is_none());
+        assert!(super::ObjectTransmissionInformation::new(u32::MAX as u64, 1, 10).is_none());
+
+        // Z inconsistent with the number of source symbols
+        let oti = super::ObjectTransmissionInformation {
+            transfer_length: 1000,
+            symbol_size: 10,
+            nb_source_blocks: 0,
+            symbols_per_packet: 1,
+            nb_sub_blocks: 1,
+            alignment: 1,
+        };
+        assert!(!oti.is_valid());
+        assert!(super::ObjectDecoder::new(oti, vec![0u8; 1000]).is_none());
+    }
+
+    #[test]
+    fn test_object_encode_decode() {
+        encode_decode(100 * 1024, 1024, 32, 2);
+    }
+
+    #[test]
+    fn test_object_encode_decode_last_block_truncated() {
+        encode_decode(10 * 1000 + 7, 64, 50, 3);
+    }
+
+    #[test]
+    fn test_object_encode_decode_small_blocks() {
+        // Source blocks of less than 4 symbols
+        for length in [1, 2, 3, 100, 128, 200, 300, 384, 385, 512, 513, 700] {
+            encode_decode(length, 128, 64, 2);
+        }
+        // 2 blocks of 3 symbols
+        encode_decode(6 * 128, 128, 4, 2);
+        // Blocks of at most 1, 2 and 3 symbols
+        for max_source_symbols in 1..4 {
+            encode_decode(1000, 100, max_source_symbols, 2);
+        }
+    }
+
+    #[test]
+    fn test_encoding_symbol_size() {
+        crate::tests::init();
+
+        // 16 symbols of 64 bytes in 2 blocks, the last symbol of the object is 40 bytes long
+        let data: Vec<u8> = (0..1000).map(|i| (i * 7) as u8).collect();
+        let encoder = super::ObjectEncoder::new(&data, 64, 8).unwrap();
+        let sub_blocks = super::ObjectEncoder::new(&data, 64, 8)
+            .unwrap()
+            .with_sub_blocks(4, 4)
+            .unwrap();
+        let oti = *encoder.transmission_information();
+        assert!(encoder.nb_source_blocks() == 2);
+
+        for sbn in 0..encoder.nb_source_blocks() {
+            let mut block_encoder = encoder.source_block_encoder(sbn).unwrap();
+            let mut sub_block_encoder = encoder.sub_block_encoder(sbn).unwrap();
+            let mut split_encoder = sub_blocks.sub_block_encoder(sbn).unwrap();
+            assert!(block_encoder.nb_source_symbols() == 8);
+            for esi in 0..16 {
+                let encoding_symbol = block_encoder.fountain(esi);
+                assert!(encoding_symbol.len() == oti.symbol_size as usize);
+                assert!(sub_block_encoder.fountain(esi) == encoding_symbol);
+                assert!(split_encoder.fountain(esi).len() == oti.symbol_size as usize);
+            }
+
+            // Source symbols are T-byte slices of the object
+            let offset = sbn as usize * 8 * 64;
+            let mut source_symbol = data[offset..(offset + 64).min(data.len())].to_vec();
+            source_symbol.resize(64, 0);
+            assert!(block_encoder.fountain(0) == source_symbol);
+        }
+
+        let mut last_symbol = data[15 * 64..].to_vec();
+        last_symbol.resize(64, 0);
+        assert!(encoder.source_block_encoder(1).unwrap().fountain(7) == last_symbol);
+    }
+
+    #[test]
+    fn test_object_symbols_per_packet() {
+        crate::tests::init();
+
+        let mut data = vec![0u8; 10 * 1024 + 3];
+        rand::thread_rng().fill_bytes(&mut data);
+        assert!(super::ObjectEncoder::new(&data, 64, 40)
+            .unwrap()
+            .with_symbols_per_packet(0)
+            .is_none());
+
+        let encoder = super::ObjectEncoder::new(&data, 64, 40)
+            .unwrap()
+            .with_symbols_per_packet(8)
+            .unwrap();
+        let oti = *encoder.transmission_information();
+        assert!(oti.symbols_per_packet == 8);
+        assert!(oti.is_valid());
+        let mut decoder = super::ObjectDecoder::new(oti, vec![0u8; data.len()]).unwrap();
+
+        let g = oti.symbols_per_packet as u32;
+        for sbn in 0..encoder.nb_source_blocks() {
+            let mut block_encoder = encoder.source_block_encoder(sbn).unwrap();
+            // Lose the first packet of each block
+            let mut esi = g;
+            while !decoder.is_block_decoded(sbn) {
+                let packet = block_encoder.fountain_group(esi, g);
+                decoder.push_encoding_symbol(sbn, esi, &packet);
+                esi += g;
+            }
+        }
+
+        assert!(decoder.finish().unwrap() == data);
+    }
+
+    #[test]
+    fn test_object_sub_blocks() {
+        crate::tests::init();
+
+        let mut data = vec![0u8; 50 * 1024 + 17];
+        rand::thread_rng().fill_bytes(&mut data);
+
+        // Blocks of 32 symbols of 256 bytes split into sub-blocks of at most 2 KiB
+        let encoder = super::ObjectEncoder::new(&data, 256, 32)
+            .unwrap()
+            .with_working_memory(4, 2048)
+            .unwrap();
+        let oti = *encoder.transmission_information();
+        assert!(oti.nb_sub_blocks == 4 && oti.alignment == 4);
+        assert!(encoder.source_block_encoder(0).is_none());
+
+        let mut decoder = super::ObjectDecoder::new(oti, vec![0u8; data.len()]).unwrap();
+        for sbn in 0..encoder.nb_source_blocks() {
+            let mut block_encoder = encoder.sub_block_encoder(sbn).unwrap();
+            assert!(block_encoder.nb_sub_blocks() == 4);
+            let mut esi = 2;
+            while !decoder.is_block_decoded(sbn) {
+                decoder.push_encoding_symbol(sbn, esi, &block_encoder.fountain(esi));
+                esi += 1;
+            }
+        }
+        assert!(decoder.finish().unwrap() == data);
+
+        let oti = super::ObjectTransmissionInformation::new(10000, 100, 50).unwrap();
+        assert!(oti.with_sub_blocks(2, 3).is_none());
+        assert!(oti.with_sub_blocks(26, 4).is_none());
+        assert!(oti.with_sub_blocks(25, 4).is_some());
+        assert!(oti.with_working_memory(4, 1).unwrap().nb_sub_blocks == 25);
+    }
+
+    #[cfg(feature = "manifest")]
+    #[test]
+    fn test_object_manifest_digest() {
+        crate::tests::init();
+
+        let mut data = vec![0u8; 20 * 1024];
+        rand::thread_rng().fill_bytes(&mut data);
+        let encoder = super::ObjectEncoder::new(&data, 512, 16).unwrap();
+        let manifest = encoder.manifest();
+
+        let push_all = |decoder: &mut super::ObjectDecoder<Vec<u8>>, corrupt: bool| {
+            for sbn in 0..encoder.nb_source_blocks() {
+                let mut block_encoder = encoder.source_block_encoder(sbn).unwrap();
+                for esi in 0..block_encoder.nb_source_symbols() {
+                    let mut encoding_symbol = block_encoder.fountain(esi);
+                    if corrupt && sbn == 0 && esi == 0 {
+                        encoding_symbol[0] ^= 0xFF;
+                    }
+                    decoder.push_encoding_symbol(sbn, esi, &encoding_symbol);
+                }
+            }
+        };
+
+        let decoder =
+            super::ObjectDecoder::from_manifest(&manifest, vec![0u8; data.len()]).unwrap();
+        assert!(decoder.finish() == Err(super::Error::Incomplete));
+
+        let mut decoder =
+            super::ObjectDecoder::from_manifest(&manifest, vec![0u8; data.len()]).unwrap();
+        push_all(&mut decoder, false);
+        assert!(decoder.finish().unwrap() == data);
+
+        // A corrupted symbol is only detected by the digest
+        let mut decoder =
+            super::ObjectDecoder::from_manifest(&manifest, vec![0u8; data.len()]).unwrap();
+        push_all(&mut decoder, true);
+        assert!(decoder.fully_decoded());
+        assert!(decoder.finish() == Err(super::Error::DigestMismatch));
+    }
+}
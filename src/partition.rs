@@ -1,4 +1,5 @@
 use crate::encodingsymbols::EncodingSymbol;
+use alloc::vec::Vec;
 
 ///
 /// Partitions a block into semi-equal pieces of symbols.
@@ -23,8 +24,8 @@ impl Partition {
     /// as well as the number of longer and shorter pieces.
     ///
     pub fn new(source_length: usize, nb_source_symbols: usize) -> Self {
-        let mut il = (source_length as f64 / nb_source_symbols as f64).ceil() as usize;
-        let mut is = (source_length as f64 / nb_source_symbols as f64).floor() as usize;
+        let mut il = source_length.div_ceil(nb_source_symbols);
+        let mut is = source_length / nb_source_symbols;
         let jl = source_length - (is * nb_source_symbols);
         let js = nb_source_symbols - jl;
 
@@ -69,16 +70,33 @@ impl Partition {
         output
     }
 
-    pub fn decode_source_block(&self, source_block: &[Vec<u8>]) -> Vec<u8> {
-        let mut out = Vec::new();
+    /// Return the byte range of the source symbol `i` inside the source block
+    pub fn symbol_range(&self, i: usize) -> core::ops::Range<usize> {
+        if i < self.nb_long {
+            let start = i * self.long_size;
+            return start..start + self.long_size;
+        }
+
+        let start = self.nb_long * self.long_size + (i - self.nb_long) * self.small_size;
+        start..start + self.small_size
+    }
 
-        assert!(self.nb_long + self.nb_small == source_block.len());
-        for i in 0..self.nb_long {
-            out.extend(source_block[i][0..self.long_size].to_vec());
+    pub fn split_source_block_mut<'a>(&self, source_data: &'a mut [u8]) -> Vec<&'a mut [u8]> {
+        let mut remaining = source_data;
+        let mut output: Vec<&mut [u8]> = Vec::new();
+
+        for _ in 0..self.nb_long {
+            let (symbol, tail) = remaining.split_at_mut(self.long_size);
+            output.push(symbol);
+            remaining = tail;
         }
-        for i in 0..self.nb_small {
-            out.extend(source_block[self.nb_long + i][0..self.small_size].to_vec());
+
+        for _ in 0..self.nb_small {
+            let (symbol, tail) = remaining.split_at_mut(self.small_size);
+            output.push(symbol);
+            remaining = tail;
         }
-        out
+
+        output
     }
 }
@@ -0,0 +1,191 @@
+//!
+//! Python module `raptor_code`
+//!
+//! Encoding symbols and source blocks are read through the buffer protocol
+//! (`bytes`, `bytearray`, `memoryview`...), and the GIL is released while encoding and decoding.
+//! Read-only buffers are borrowed without copy. Writable buffers are copied first,
+//! since another Python thread can modify them once the GIL is released.
+//!
+
+use crate::encodingsymbols::EncodingSymbol;
+use crate::{raptor, SourceBlockDecoder, SourceBlockEncoder};
+use pyo3::buffer::PyBuffer;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::borrow::Cow;
+
+/// Return the content of a buffer that can be read without the GIL
+///
+/// Read-only buffers are borrowed without copy, writable buffers are copied.
+fn buffer_data<'a>(py: Python<'_>, buffer: &'a PyBuffer<u8>) -> PyResult<Cow<'a, [u8]>> {
+    if !buffer.readonly() {
+        return Ok(Cow::Owned(buffer.to_vec(py)?));
+    }
+    if !buffer.is_c_contiguous() {
+        return Err(PyValueError::new_err("buffer must be C contiguous"));
+    }
+
+    // The buffer is exported and read-only, its memory stays valid and unchanged while `buffer` is alive
+    let data =
+        unsafe { std::slice::from_raw_parts(buffer.buf_ptr() as *const u8, buffer.len_bytes()) };
+    Ok(Cow::Borrowed(data))
+}
+
+/// Check a number of source symbols, blocks of less than 4 symbols are padded by the encoder and the decoder
+fn check_nb_source_symbols(nb_source_symbols: usize) -> PyResult<()> {
+    if nb_source_symbols == 0 || nb_source_symbols > crate::MAX_SOURCE_SYMBOLS_PER_BLOCK as usize {
+        return Err(PyValueError::new_err(
+            "number of source symbols out of range",
+        ));
+    }
+    Ok(())
+}
+
+/// Encode a source block, return the encoding symbols and the number of source symbols
+#[pyfunction]
+fn encode_source_block<'py>(
+    py: Python<'py>,
+    source_block: PyBuffer<u8>,
+    max_source_symbols: usize,
+    nb_repair: usize,
+) -> PyResult<(Vec<Bound<'py, PyBytes>>, u32)> {
+    check_nb_source_symbols(max_source_symbols)?;
+    let source_block = buffer_data(py, &source_block)?;
+    if source_block.is_empty() {
+        return Err(PyValueError::new_err("source block is empty"));
+    }
+
+    let (encoding_symbols, nb_source_symbols) = py
+        .allow_threads(|| crate::encode_source_block(&source_block, max_source_symbols, nb_repair));
+    let encoding_symbols = encoding_symbols
+        .iter()
+        .map(|symbol| PyBytes::new(py, symbol))
+        .collect();
+    Ok((encoding_symbols, nb_source_symbols))
+}
+
+/// Decode a source block from a list of encoding symbols indexed by ESI, lost symbols are `None`
+///
+/// Return `None` if the source block cannot be decoded
+#[pyfunction]
+fn decode_source_block<'py>(
+    py: Python<'py>,
+    encoding_symbols: Vec<Option<PyBuffer<u8>>>,
+    nb_source_symbols: usize,
+    source_block_length: usize,
+) -> PyResult<Option<Bound<'py, PyBytes>>> {
+    check_nb_source_symbols(nb_source_symbols)?;
+
+    let mut data = Vec::new();
+    for (esi, buffer) in encoding_symbols.iter().enumerate() {
+        if let Some(buffer) = buffer {
+            data.push((esi as u32, buffer_data(py, buffer)?));
+        }
+    }
+    let symbols: Vec<EncodingSymbol> = data
+        .iter()
+        .map(|(esi, symbol)| EncodingSymbol::new(symbol, *esi))
+        .collect();
+
+    let source_block = py.allow_threads(|| {
+        let mut raptor = raptor::Raptor::new(nb_source_symbols as u32);
+        raptor.add_encoding_symbols(&symbols);
+        raptor.decode(source_block_length)
+    });
+    Ok(source_block.map(|source_block| PyBytes::new(py, &source_block)))
+}
+
+/// On the fly source block encoder
+#[pyclass(name = "SourceBlockEncoder")]
+struct PySourceBlockEncoder {
+    encoder: SourceBlockEncoder,
+}
+
+#[pymethods]
+impl PySourceBlockEncoder {
+    #[new]
+    fn new(
+        py: Python<'_>,
+        source_block: PyBuffer<u8>,
+        max_source_symbols: usize,
+    ) -> PyResult<Self> {
+        check_nb_source_symbols(max_source_symbols)?;
+        let source_block = buffer_data(py, &source_block)?;
+        if source_block.is_empty() {
+            return Err(PyValueError::new_err("source block is empty"));
+        }
+
+        let encoder =
+            py.allow_threads(|| SourceBlockEncoder::new(&source_block, max_source_symbols));
+        Ok(PySourceBlockEncoder { encoder })
+    }
+
+    /// Number of source symbols (k) inside the block
+    #[getter]
+    fn nb_source_symbols(&self) -> u32 {
+        self.encoder.nb_source_symbols()
+    }
+
+    /// Generate the encoding symbol `esi`
+    fn fountain<'py>(&mut self, py: Python<'py>, esi: u32) -> Bound<'py, PyBytes> {
+        let encoder = &mut self.encoder;
+        let symbol = py.allow_threads(|| encoder.fountain(esi));
+        PyBytes::new(py, &symbol)
+    }
+}
+
+/// On the fly source block decoder
+#[pyclass(name = "SourceBlockDecoder")]
+struct PySourceBlockDecoder {
+    decoder: SourceBlockDecoder,
+}
+
+#[pymethods]
+impl PySourceBlockDecoder {
+    #[new]
+    fn new(nb_source_symbols: usize) -> PyResult<Self> {
+        check_nb_source_symbols(nb_source_symbols)?;
+        Ok(PySourceBlockDecoder {
+            decoder: SourceBlockDecoder::new(nb_source_symbols),
+        })
+    }
+
+    /// Push an encoding symbol to the decoder
+    fn push_encoding_symbol(
+        &mut self,
+        py: Python<'_>,
+        encoding_symbol: PyBuffer<u8>,
+        esi: u32,
+    ) -> PyResult<()> {
+        let encoding_symbol = buffer_data(py, &encoding_symbol)?;
+        let decoder = &mut self.decoder;
+        py.allow_threads(|| decoder.push_encoding_symbol(&encoding_symbol, esi));
+        Ok(())
+    }
+
+    /// Return true when the block can be fully decoded
+    fn fully_specified(&self) -> bool {
+        self.decoder.fully_specified()
+    }
+
+    /// Decode the source block, return `None` if the block cannot be decoded yet
+    fn decode<'py>(
+        &mut self,
+        py: Python<'py>,
+        source_block_length: usize,
+    ) -> Option<Bound<'py, PyBytes>> {
+        let decoder = &mut self.decoder;
+        let source_block = py.allow_threads(|| decoder.decode(source_block_length))?;
+        Some(PyBytes::new(py, &source_block))
+    }
+}
+
+#[pymodule]
+fn raptor_code(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(encode_source_block, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_source_block, m)?)?;
+    m.add_class::<PySourceBlockEncoder>()?;
+    m.add_class::<PySourceBlockDecoder>()?;
+    Ok(())
+}
@@ -2,8 +2,41 @@ use crate::common;
 use crate::encodingsymbols::EncodingSymbol;
 use crate::partition::Partition;
 use crate::sparse_matrix::SparseMatrix;
+use crate::MIN_SOURCE_SYMBOLS_PER_BLOCK;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::sync::{Arc, Mutex, OnceLock};
 
+/// Max number of source block sizes (K) kept in the precode cache
+#[cfg(feature = "std")]
+const MAX_CACHED_PRECODES: usize = 256;
+
+/// Initial decoding matrix (LDPC and Half constraints) shared by all the blocks of K source symbols
+#[cfg(feature = "std")]
+static PRECODE_CACHE: OnceLock<Mutex<HashMap<u32, Arc<Raptor>>>> = OnceLock::new();
+
+/// Return the number of source symbols of the LT code of a block of `nb_source_symbols` symbols
+///
+/// The systematic indices of RFC 5053 section 5.7 are defined from K = 4, smaller blocks are padded
+/// with zero symbols known by the encoder and the decoder, that are never sent.
+pub fn padded_k(nb_source_symbols: u32) -> u32 {
+    nb_source_symbols.max(MIN_SOURCE_SYMBOLS_PER_BLOCK)
+}
+
+/// Return the ESI of the encoding symbol `esi` inside the padded block, the repair symbols follow the padding symbols
+pub fn padded_esi(nb_source_symbols: u32, esi: u32) -> u32 {
+    if esi < nb_source_symbols {
+        return esi;
+    }
+    esi.wrapping_add(padded_k(nb_source_symbols) - nb_source_symbols)
+}
+
+#[derive(Clone)]
 pub struct Raptor {
+    nb_source_symbols: u32,
     k: u32,
     l: u32,
     l_prime: u32,
@@ -11,7 +44,41 @@ pub struct Raptor {
 }
 
 impl Raptor {
+    /// Create a decoding matrix for a block of `k` source symbols
+    ///
+    /// The precode constraints only depend on K, they are generated once
+    /// and cloned from a cache shared by all threads.
+    #[cfg(feature = "std")]
     pub fn new(k: u32) -> Self {
+        let cache = PRECODE_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+        let cached = cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&k)
+            .cloned();
+        if let Some(precode) = cached {
+            return precode.as_ref().clone();
+        }
+
+        let precode = Raptor::precode(k);
+        let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+        if cache.len() < MAX_CACHED_PRECODES {
+            cache.insert(k, Arc::new(precode.clone()));
+        }
+        precode
+    }
+
+    /// Create a decoding matrix for a block of `k` source symbols
+    #[cfg(not(feature = "std"))]
+    pub fn new(k: u32) -> Self {
+        Raptor::precode(k)
+    }
+
+    /// Generate the precode constraints of a block of `nb_source_symbols` source symbols
+    ///
+    /// The padding symbols of a block of less than 4 symbols are added as known zero symbols.
+    fn precode(nb_source_symbols: u32) -> Self {
+        let k = padded_k(nb_source_symbols);
         let (l, l_prime, s, h, hp) = common::intermediate_symbols(k);
         let mut matrix = SparseMatrix::new(l as usize);
 
@@ -38,13 +105,9 @@ impl Raptor {
         // G_LDPC
         let mut composition: Vec<Vec<u32>> = vec![Vec::new(); s as usize];
         for i in 0..k {
-            let a = 1 + (i as f64 / s as f64).floor() as u32 % (s - 1);
-            let b = i % s;
-            composition[b as usize].push(i);
-            let b = (b + a) % s;
-            composition[b as usize].push(i);
-            let b = (b + a) % s;
-            composition[b as usize].push(i);
+            for b in common::ldpc_rows(i, s) {
+                composition[b as usize].push(i);
+            }
         }
 
         for i in 0..s {
@@ -63,17 +126,30 @@ impl Raptor {
                 }
             }
             compositions[i as usize].push(k + s + i);
-            matrix.add_equation(compositions[i as usize].clone(), Vec::new())
+            matrix.add_equation(compositions[i as usize].clone(), Vec::new());
+        }
+
+        for esi in nb_source_symbols..k {
+            matrix.add_equation(common::find_lt_indices(k, esi, l, l_prime), Vec::new());
         }
 
-        let raptor = Raptor {
+        Raptor {
+            nb_source_symbols,
             k,
             l,
             l_prime,
             matrix,
-        };
+        }
+    }
+
+    /// Return the number of source symbols of the LT code, including the padding symbols
+    pub fn get_k(&self) -> u32 {
+        self.k
+    }
 
-        raptor
+    /// Return the number of source symbols of the block
+    pub fn nb_source_symbols(&self) -> u32 {
+        self.nb_source_symbols
     }
 
     pub fn get_l(&self) -> u32 {
@@ -85,9 +161,13 @@ impl Raptor {
     }
 
     pub fn add_encoding_symbol(&mut self, encoding_symbol: &EncodingSymbol) {
-        let indices = common::find_lt_indices(self.k, encoding_symbol.esi, self.l, self.l_prime);
-        self.matrix
-            .add_equation(indices, encoding_symbol.data.to_vec());
+        self.add_encoding_symbol_owned(encoding_symbol.data.to_vec(), encoding_symbol.esi);
+    }
+
+    pub fn add_encoding_symbol_owned(&mut self, data: Vec<u8>, esi: u32) -> Option<Vec<u8>> {
+        let esi = padded_esi(self.nb_source_symbols, esi);
+        let indices = common::find_lt_indices(self.k, esi, self.l, self.l_prime);
+        self.matrix.add_equation(indices, data)
     }
 
     pub fn add_encoding_symbols(&mut self, encoding_symbols: &[EncodingSymbol]) -> bool {
@@ -101,31 +181,65 @@ impl Raptor {
         self.matrix.reduce()
     }
 
-    pub fn intermediate_symbols(&self) -> &[Vec<u8>] {
-        &self.matrix.intermediate
+    pub fn into_intermediate_symbols(self) -> Vec<Vec<u8>> {
+        self.matrix.intermediate
     }
 
     pub fn decode(&mut self, size: usize) -> Option<Vec<u8>> {
-        if !self.matrix.fully_specified() {
+        let mut source_block = vec![0; size];
+        if !self.decode_into(&mut source_block) {
             return None;
         }
+        Some(source_block)
+    }
+
+    pub fn decode_into(&mut self, source_block: &mut [u8]) -> bool {
+        let partition = Partition::new(source_block.len(), self.nb_source_symbols as usize);
+        let mut source_symbols = partition.split_source_block_mut(source_block);
+        self.decode_symbols_into(&mut source_symbols)
+    }
+
+    pub fn decode_symbols_into(&mut self, source_symbols: &mut [&mut [u8]]) -> bool {
+        if !self.matrix.fully_specified() {
+            return false;
+        }
 
         self.reduce();
 
-        let mut source_block: Vec<Vec<u8>> = Vec::new();
-        for i in 0..self.k {
-            let block =
-                common::lt_encode(self.k, i, self.l, self.l_prime, &self.matrix.intermediate);
-            source_block.push(block);
+        for (i, symbol) in source_symbols
+            .iter_mut()
+            .enumerate()
+            .take(self.nb_source_symbols as usize)
+        {
+            common::lt_encode_into(
+                self.k,
+                i as u32,
+                self.l,
+                self.l_prime,
+                &self.matrix.intermediate,
+                symbol,
+            );
         }
+        true
+    }
 
-        let partition = Partition::new(size, self.k as usize);
-        Some(partition.decode_source_block(&source_block))
+    /// Check that an encoding symbol matches the intermediate symbols, the matrix must be reduced
+    pub fn is_consistent(&self, esi: u32, data: &[u8]) -> bool {
+        let esi = padded_esi(self.nb_source_symbols, esi);
+        let encoded =
+            common::lt_encode(self.k, esi, self.l, self.l_prime, &self.matrix.intermediate);
+        data.iter()
+            .enumerate()
+            .all(|(i, v)| *v == encoded.get(i).copied().unwrap_or(0))
     }
 
     pub fn fully_specified(&self) -> bool {
         self.matrix.fully_specified()
     }
+
+    pub fn rank(&self) -> u32 {
+        self.matrix.rank as u32
+    }
 }
 
 #[cfg(test)]
@@ -140,9 +254,9 @@ mod tests {
     fn test_raptor_matrix() {
         crate::tests::init();
         let raptor = super::Raptor::new(10);
-        assert!(raptor.matrix.coeff[0] == vec![0, 5, 6, 7, 10]);
-        assert!(raptor.matrix.coeff[1] == vec![1, 2, 3, 8, 13]);
-        assert!(raptor.matrix.coeff[2] == vec![2, 3, 4, 7, 9, 14]);
+        assert!(raptor.matrix.coeff[0].iter().eq([0, 5, 6, 7, 10]));
+        assert!(raptor.matrix.coeff[1].iter().eq([1, 2, 3, 8, 13]));
+        assert!(raptor.matrix.coeff[2].iter().eq([2, 3, 4, 7, 9, 14]));
     }
 
     #[test]
@@ -168,8 +282,21 @@ mod tests {
     #[test]
     fn test_decode_empty() {
         let mut raptor = super::Raptor::new(64);
-        assert!(raptor.fully_specified() == false);
+        assert!(!raptor.fully_specified());
         let out = raptor.decode(1024);
         assert!(out.is_none());
     }
+
+    #[test]
+    fn test_precode_cache() {
+        let precode = super::Raptor::precode(100);
+        let mut raptor = super::Raptor::new(100);
+        assert!(raptor.matrix.coeff == precode.matrix.coeff);
+        assert!(raptor.matrix.rank == precode.matrix.rank);
+
+        // A decoder does not modify the cached matrix
+        raptor.add_encoding_symbol_owned(vec![1, 2, 3], 0);
+        let raptor = super::Raptor::new(100);
+        assert!(raptor.matrix.coeff == precode.matrix.coeff);
+    }
 }
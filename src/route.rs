@@ -0,0 +1,843 @@
+//!
+//! ROUTE source and repair flows
+//!
+//! Real-Time Object Delivery over Unidirectional Transport (ATSC A/331 section 8 and annex A) delivers objects,
+//! such as media segments, in ALC/LCT packets. Every packet carries the length of its object in the EXT_TOL
+//! header extension.
+//!
+//! * The source flow carries the object itself, every source packet carries a contiguous range of the object
+//!   identified by its start offset.
+//! * The repair flow carries Raptor repair symbols of the FEC transport object: the object padded with zeros
+//!   and followed by its length, so the object can be recovered when no source packet is received.
+//!   The repair packets carry the Raptor FEC Payload ID of [`crate::alc`].
+//!
+//! ```text
+//! Source FEC Payload ID                    FEC transport object
+//! +-----------------------------------+    +---------------------+---------+--------+
+//! |          start_offset (32)        |    |        object       | padding | F (32) |
+//! +-----------------------------------+    +---------------------+---------+--------+
+//! ```
+//!
+
+use crate::alc::{AlcPacket, LctHeader};
+use crate::error::Error;
+use crate::object::{ObjectDecoder, ObjectEncoder, ObjectTransmissionInformation};
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Codepoint of a Non Real Time file delivered in File Mode, ATSC A/331 table A.3.6
+pub const CODEPOINT_NRT_FILE: u8 = 1;
+
+/// Codepoint of a Non Real Time file delivered in Entity Mode
+pub const CODEPOINT_NRT_ENTITY: u8 = 2;
+
+/// Codepoint of an unsigned package
+pub const CODEPOINT_NRT_UNSIGNED_PACKAGE: u8 = 3;
+
+/// Codepoint of a signed package
+pub const CODEPOINT_NRT_SIGNED_PACKAGE: u8 = 4;
+
+/// Codepoint of a new Initialization Segment, the timeline of the media is changed
+pub const CODEPOINT_NEW_INIT_SEGMENT_TIMELINE_CHANGED: u8 = 5;
+
+/// Codepoint of a new Initialization Segment, the timeline of the media is continued
+pub const CODEPOINT_NEW_INIT_SEGMENT_TIMELINE_CONTINUED: u8 = 6;
+
+/// Codepoint of a repeated Initialization Segment
+pub const CODEPOINT_REDUNDANT_INIT_SEGMENT: u8 = 7;
+
+/// Codepoint of a Media Segment delivered in File Mode
+pub const CODEPOINT_MEDIA_SEGMENT_FILE: u8 = 8;
+
+/// Codepoint of a Media Segment delivered in Entity Mode
+pub const CODEPOINT_MEDIA_SEGMENT_ENTITY: u8 = 9;
+
+/// Default max size of an object accepted by [`RouteDecoder`], 64 MiB
+pub const DEFAULT_MAX_OBJECT_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Size of the start offset of a source packet
+const START_OFFSET_SIZE: usize = 4;
+
+/// Size of the object length appended to the FEC transport object
+const OBJECT_LENGTH_SIZE: usize = 4;
+
+/// Max number of objects tracked by the decoder
+const MAX_OBJECTS: usize = 32;
+
+///
+/// FEC parameters of a repair flow
+///
+/// The parameters are signaled out of band, in the `RepairFlow` element of the S-TSID.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RepairFlow {
+    /// Transport Session Identifier (TSI) of the repair flow
+    pub tsi: u64,
+    /// Size of a repair symbol in bytes (T)
+    pub symbol_size: u16,
+    /// Max number of source symbols inside a source block
+    pub max_source_symbols_per_block: u32,
+    /// Number of repair symbols of a source block, in percent of its number of source symbols
+    pub overhead: u32,
+}
+
+impl RepairFlow {
+    /// Create the FEC parameters of a repair flow
+    ///
+    /// # Parameters
+    ///
+    /// * `tsi`: Transport Session Identifier of the repair flow.
+    /// * `symbol_size`: Size of a repair symbol in bytes (T).
+    /// * `max_source_symbols_per_block`: Max number of source symbols inside a source block, at most [`crate::MAX_SOURCE_SYMBOLS_PER_BLOCK`].
+    /// * `overhead`: Number of repair symbols of a source block, in percent of its number of source symbols.
+    ///
+    /// # Returns
+    ///
+    /// * `None` if the parameters are out of range or the encoding symbols of a source block cannot be identified by a 16-bit ESI
+    /// * `Some(RepairFlow)` otherwise
+    pub fn new(
+        tsi: u64,
+        symbol_size: u16,
+        max_source_symbols_per_block: u32,
+        overhead: u32,
+    ) -> Option<Self> {
+        if symbol_size == 0
+            || max_source_symbols_per_block == 0
+            || max_source_symbols_per_block > crate::MAX_SOURCE_SYMBOLS_PER_BLOCK
+        {
+            return None;
+        }
+
+        let max_encoding_symbols = max_source_symbols_per_block as u64
+            + (max_source_symbols_per_block as u64 * overhead as u64).div_ceil(100);
+        if max_encoding_symbols > u16::MAX as u64 + 1 {
+            return None;
+        }
+
+        Some(RepairFlow {
+            tsi,
+            symbol_size,
+            max_source_symbols_per_block,
+            overhead,
+        })
+    }
+
+    /// Return the length of the FEC transport object of an object of `object_length` bytes
+    pub fn fec_transport_object_length(&self, object_length: u64) -> u64 {
+        let symbol_size = self.symbol_size as u64;
+        (object_length + OBJECT_LENGTH_SIZE as u64).div_ceil(symbol_size) * symbol_size
+    }
+
+    /// Return the transmission information of a FEC transport object of `length` bytes
+    fn transmission_information(&self, length: u64) -> Option<ObjectTransmissionInformation> {
+        let symbol_size = self.symbol_size as u64;
+        if length == 0 || !length.is_multiple_of(symbol_size) {
+            return None;
+        }
+        ObjectTransmissionInformation::new(
+            length,
+            self.symbol_size,
+            self.max_source_symbols_per_block,
+        )
+    }
+}
+
+///
+/// Packet of a ROUTE source flow
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourcePacket<'a> {
+    /// LCT header, the transfer object length is the length of the object
+    pub header: LctHeader,
+    /// Offset of the payload inside the object
+    pub start_offset: u32,
+    /// Range of the object starting at `start_offset`
+    pub payload: &'a [u8],
+}
+
+impl<'a> SourcePacket<'a> {
+    /// Create a packet carrying the range of an object starting at `start_offset`
+    pub fn new(header: LctHeader, start_offset: u32, payload: &'a [u8]) -> Self {
+        SourcePacket {
+            header,
+            start_offset,
+            payload,
+        }
+    }
+
+    /// Serialize the packet
+    ///
+    /// # Returns
+    ///
+    /// * `Err(Error::InvalidPacket)` if a field of the header is out of range
+    /// * `Ok(Vec<u8>)` the packet
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut output = Vec::with_capacity(64 + self.payload.len());
+        self.header.write(&mut output)?;
+        output.extend_from_slice(&self.start_offset.to_be_bytes());
+        output.extend_from_slice(self.payload);
+        Ok(output)
+    }
+
+    /// Parse a packet, the payload is borrowed from `data`
+    ///
+    /// # Returns
+    ///
+    /// * `Err(Error::InvalidPacket)` if the packet is malformed or truncated
+    /// * `Ok(SourcePacket)` otherwise
+    pub fn parse(data: &'a [u8]) -> Result<Self, Error> {
+        let (header, length) = LctHeader::parse(data)?;
+        let start_offset = data
+            .get(length..length + START_OFFSET_SIZE)
+            .ok_or(Error::InvalidPacket)?;
+
+        Ok(SourcePacket {
+            header,
+            start_offset: u32::from_be_bytes(start_offset.try_into().unwrap()),
+            payload: &data[length + START_OFFSET_SIZE..],
+        })
+    }
+}
+
+///
+/// Packetizer of the objects of a ROUTE source flow and of its optional repair flow
+///
+#[derive(Debug, Clone)]
+pub struct RouteEncoder {
+    tsi: u64,
+    codepoint: u8,
+    payload_size: usize,
+    repair: Option<RepairFlow>,
+}
+
+impl RouteEncoder {
+    /// Create a ROUTE packetizer
+    ///
+    /// # Parameters
+    ///
+    /// * `tsi`: Transport Session Identifier of the source flow.
+    /// * `codepoint`: Codepoint of the source packets, for example [`CODEPOINT_MEDIA_SEGMENT_FILE`].
+    /// * `payload_size`: Max number of bytes of the object carried by a source packet.
+    ///
+    /// # Returns
+    ///
+    /// * `None` if `payload_size` is 0
+    /// * `Some(RouteEncoder)` otherwise
+    pub fn new(tsi: u64, codepoint: u8, payload_size: usize) -> Option<Self> {
+        if payload_size == 0 {
+            return None;
+        }
+        Some(RouteEncoder {
+            tsi,
+            codepoint,
+            payload_size,
+            repair: None,
+        })
+    }
+
+    /// Protect the source flow with a repair flow
+    ///
+    /// Source packets must carry whole symbols so the receiver can combine them with the repair symbols.
+    ///
+    /// # Returns
+    ///
+    /// * `None` if the payload size is not a multiple of the symbol size of the repair flow
+    /// * `Some(RouteEncoder)` otherwise
+    pub fn with_repair_flow(mut self, repair: RepairFlow) -> Option<Self> {
+        if !self
+            .payload_size
+            .is_multiple_of(repair.symbol_size as usize)
+        {
+            return None;
+        }
+        self.repair = Some(repair);
+        Some(self)
+    }
+
+    /// Generate the source packets of the object `toi`
+    ///
+    /// The last packet sets the Close Object flag.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(Error::InvalidPacket)` if the object is larger than 4 GiB or the header cannot be written
+    /// * `Ok(Vec<Vec<u8>>)` the source packets
+    pub fn source_packets(&self, toi: u64, object: &[u8]) -> Result<Vec<Vec<u8>>, Error> {
+        if object.len() > u32::MAX as usize {
+            return Err(Error::InvalidPacket);
+        }
+
+        let nb_packets = object.len().div_ceil(self.payload_size).max(1);
+        (0..nb_packets)
+            .map(|i| {
+                let start_offset = i * self.payload_size;
+                let end = (start_offset + self.payload_size).min(object.len());
+                let header = LctHeader {
+                    tsi: self.tsi,
+                    toi,
+                    codepoint: self.codepoint,
+                    close_object: i + 1 == nb_packets,
+                    transfer_object_length: Some(object.len() as u64),
+                    ..Default::default()
+                };
+                SourcePacket::new(header, start_offset as u32, &object[start_offset..end])
+                    .to_bytes()
+            })
+            .collect()
+    }
+
+    /// Generate the repair packets of the object `toi`
+    ///
+    /// # Returns
+    ///
+    /// * `Err(Error::InvalidPacket)` if the object cannot be protected with the parameters of the repair flow
+    /// * `Ok(Vec<Vec<u8>>)` the repair packets, empty when the source flow is not protected
+    pub fn repair_packets(&self, toi: u64, object: &[u8]) -> Result<Vec<Vec<u8>>, Error> {
+        let Some(repair) = &self.repair else {
+            return Ok(Vec::new());
+        };
+        if object.len() > u32::MAX as usize {
+            return Err(Error::InvalidPacket);
+        }
+
+        let length = repair.fec_transport_object_length(object.len() as u64) as usize;
+        let mut fec_transport_object = Vec::with_capacity(length);
+        fec_transport_object.extend_from_slice(object);
+        fec_transport_object.resize(length - OBJECT_LENGTH_SIZE, 0);
+        fec_transport_object.extend_from_slice(&(object.len() as u32).to_be_bytes());
+
+        let encoder = ObjectEncoder::new(
+            &fec_transport_object,
+            repair.symbol_size,
+            repair.max_source_symbols_per_block,
+        )
+        .ok_or(Error::InvalidPacket)?;
+
+        let mut packets = Vec::new();
+        for sbn in 0..encoder.nb_source_blocks() {
+            let mut block_encoder = encoder.source_block_encoder(sbn).unwrap();
+            let k = block_encoder.nb_source_symbols();
+            let nb_repair = (k * repair.overhead).div_ceil(100);
+            for esi in k..k + nb_repair {
+                let mut header = LctHeader::new(repair.tsi, toi);
+                header.transfer_object_length = Some(length as u64);
+                let encoding_symbol = block_encoder.fountain(esi);
+                let packet = AlcPacket::new(header, sbn as u16, esi as u16, &encoding_symbol);
+                packets.push(packet.to_bytes()?);
+            }
+        }
+        Ok(packets)
+    }
+}
+
+///
+/// Object delivered by a ROUTE session
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteObject {
+    /// Transport Object Identifier (TOI)
+    pub toi: u64,
+    /// Codepoint of the source packets, `None` if the object is recovered from the repair flow only
+    pub codepoint: Option<u8>,
+    /// Content of the object
+    pub data: Vec<u8>,
+}
+
+/// Reception state of an object
+#[derive(Default)]
+struct ObjectState {
+    codepoint: Option<u8>,
+    /// Object received from the source flow, allocated once its length is known
+    data: Option<Vec<u8>>,
+    /// Start offset and length of the disjoint ranges of the object received
+    ranges: BTreeMap<u32, usize>,
+    /// Number of bytes of the object covered by `ranges`
+    nb_received: usize,
+    /// Decoder of the FEC transport object
+    fec: Option<ObjectDecoder<Vec<u8>>>,
+    delivered: bool,
+}
+
+///
+/// Receiver of a ROUTE source flow and of its optional repair flow
+///
+/// Objects are delivered as soon as all their source packets are received,
+/// or when enough repair packets are received to recover them.
+///
+pub struct RouteDecoder {
+    tsi: u64,
+    repair: Option<RepairFlow>,
+    max_object_size: u64,
+    objects: BTreeMap<u64, ObjectState>,
+    delivered: Vec<RouteObject>,
+}
+
+impl RouteDecoder {
+    /// Create a receiver of the source flow `tsi`
+    pub fn new(tsi: u64) -> Self {
+        RouteDecoder {
+            tsi,
+            repair: None,
+            max_object_size: DEFAULT_MAX_OBJECT_SIZE,
+            objects: BTreeMap::new(),
+            delivered: Vec::new(),
+        }
+    }
+
+    /// Receive the repair flow protecting the source flow
+    pub fn with_repair_flow(mut self, repair: RepairFlow) -> Self {
+        self.repair = Some(repair);
+        self
+    }
+
+    /// Set the max size of an object, [`DEFAULT_MAX_OBJECT_SIZE`] by default
+    ///
+    /// Packets of larger objects are rejected before the object is allocated.
+    pub fn with_max_object_size(mut self, max_object_size: u64) -> Self {
+        self.max_object_size = max_object_size;
+        self
+    }
+
+    /// Push a packet of the source flow or of the repair flow
+    ///
+    /// Packets of other transport sessions are ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(Error::InvalidPacket)` if the packet is malformed, does not carry the transfer object length
+    ///   or belongs to an object larger than the max object size
+    /// * `Ok(())` otherwise
+    pub fn push(&mut self, data: &[u8]) -> Result<(), Error> {
+        let (header, _) = LctHeader::parse(data)?;
+        if header.tsi == self.tsi {
+            self.push_source(SourcePacket::parse(data)?)
+        } else if self.repair.is_some_and(|repair| repair.tsi == header.tsi) {
+            self.push_repair(AlcPacket::parse(data)?)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Take the objects delivered since the last call
+    pub fn take_objects(&mut self) -> Vec<RouteObject> {
+        core::mem::take(&mut self.delivered)
+    }
+
+    fn push_source(&mut self, packet: SourcePacket) -> Result<(), Error> {
+        let length = packet
+            .header
+            .transfer_object_length
+            .filter(|length| *length <= u32::MAX as u64 && *length <= self.max_object_size)
+            .ok_or(Error::InvalidPacket)? as usize;
+        let start = packet.start_offset as usize;
+        let end = start + packet.payload.len();
+        if end > length {
+            return Err(Error::InvalidPacket);
+        }
+
+        let toi = packet.header.toi;
+        let state = self.object_state(toi);
+        if state.delivered {
+            return Ok(());
+        }
+        state.codepoint = Some(packet.header.codepoint);
+
+        let data = state.data.get_or_insert_with(|| vec![0; length]);
+        if data.len() != length {
+            return Err(Error::InvalidPacket);
+        }
+        let Some((merged_start, merged_end, nb_new)) = insert_range(&mut state.ranges, start, end)
+        else {
+            return Ok(());
+        };
+        data[start..end].copy_from_slice(packet.payload);
+        state.nb_received += nb_new;
+        if let Some(fec) = &mut state.fec {
+            push_source_symbols(fec, data, merged_start, merged_end);
+        }
+
+        self.deliver(toi)?;
+
+        // The FEC decoder is only needed while the object is incomplete, the source packets are delivered without it
+        let Some(repair) = self.repair else {
+            return Ok(());
+        };
+        let Some(state) = self.objects.get_mut(&toi) else {
+            return Ok(());
+        };
+        if let (None, Some(data)) = (&state.fec, &state.data) {
+            let length = repair.fec_transport_object_length(length as u64);
+            match fec_decoder(&repair, length, data, &state.ranges) {
+                Ok(fec) => state.fec = Some(fec),
+                Err(_e) => {
+                    #[cfg(feature = "log")]
+                    log::warn!("Cannot decode the repair flow of object {}: {:?}", toi, _e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn push_repair(&mut self, packet: AlcPacket) -> Result<(), Error> {
+        let repair = self.repair.ok_or(Error::InvalidPacket)?;
+        let length = packet
+            .header
+            .transfer_object_length
+            .filter(|length| *length <= repair.fec_transport_object_length(self.max_object_size))
+            .ok_or(Error::InvalidPacket)?;
+
+        let state = self.object_state(packet.header.toi);
+        if state.delivered {
+            return Ok(());
+        }
+
+        let fec = match &mut state.fec {
+            Some(fec) => fec,
+            None => {
+                let data = state.data.as_deref().unwrap_or_default();
+                state
+                    .fec
+                    .insert(fec_decoder(&repair, length, data, &state.ranges)?)
+            }
+        };
+        if fec.transmission_information().transfer_length != length {
+            return Err(Error::InvalidPacket);
+        }
+        fec.push_encoding_symbol(packet.sbn as u32, packet.esi as u32, packet.payload);
+
+        self.deliver(packet.header.toi)
+    }
+
+    /// Return the state of the object `toi`, the oldest object is dropped when too many objects are tracked
+    fn object_state(&mut self, toi: u64) -> &mut ObjectState {
+        if !self.objects.contains_key(&toi) && self.objects.len() == MAX_OBJECTS {
+            self.objects.pop_first();
+        }
+        self.objects.entry(toi).or_default()
+    }
+
+    /// Deliver the object `toi` if it is complete or recovered
+    fn deliver(&mut self, toi: u64) -> Result<(), Error> {
+        let max_object_size = self.max_object_size;
+        let Some(state) = self.objects.get_mut(&toi) else {
+            return Ok(());
+        };
+
+        let data = match (&mut state.data, &mut state.fec) {
+            (Some(data), _) if state.nb_received == data.len() => core::mem::take(data),
+            (_, Some(fec)) if fec.fully_decoded() => {
+                let mut fec_transport_object = state.fec.take().unwrap().finish()?;
+                let length = fec_transport_object.len() - OBJECT_LENGTH_SIZE;
+                let object_length =
+                    u32::from_be_bytes(fec_transport_object[length..].try_into().unwrap()) as usize;
+                if object_length > length || object_length as u64 > max_object_size {
+                    *state = ObjectState::default();
+                    return Err(Error::InvalidPacket);
+                }
+                fec_transport_object.truncate(object_length);
+                fec_transport_object
+            }
+            _ => return Ok(()),
+        };
+
+        self.delivered.push(RouteObject {
+            toi,
+            codepoint: state.codepoint,
+            data,
+        });
+        *state = ObjectState {
+            delivered: true,
+            ..Default::default()
+        };
+        Ok(())
+    }
+}
+
+/// Merge the range `start..end` of an object with the disjoint ranges already received
+///
+/// # Returns
+///
+/// * `None` if the range was already received
+/// * `Some((start, end, nb_new))` the merged range containing `start..end` and the number of bytes not received before
+fn insert_range(
+    ranges: &mut BTreeMap<u32, usize>,
+    start: usize,
+    end: usize,
+) -> Option<(usize, usize, usize)> {
+    let mut merged = (start, end);
+    let mut nb_covered = 0;
+    let mut overlapping = Vec::new();
+    for (range_start, range_length) in ranges.range(..=end as u32).rev() {
+        let range_start = *range_start as usize;
+        let range_end = range_start + range_length;
+        if range_end < start {
+            break;
+        }
+        nb_covered += range_end.min(end).saturating_sub(range_start.max(start));
+        merged = (merged.0.min(range_start), merged.1.max(range_end));
+        overlapping.push(range_start as u32);
+    }
+    if nb_covered == end - start {
+        return None;
+    }
+
+    for range_start in overlapping {
+        ranges.remove(&range_start);
+    }
+    ranges.insert(merged.0 as u32, merged.1 - merged.0);
+    Some((merged.0, merged.1, end - start - nb_covered))
+}
+
+/// Create the decoder of a FEC transport object of `length` bytes, with the source packets already received
+fn fec_decoder(
+    repair: &RepairFlow,
+    length: u64,
+    data: &[u8],
+    ranges: &BTreeMap<u32, usize>,
+) -> Result<ObjectDecoder<Vec<u8>>, Error> {
+    let oti = repair
+        .transmission_information(length)
+        .ok_or(Error::InvalidPacket)?;
+    let mut fec = ObjectDecoder::new(oti, vec![0; length as usize]).ok_or(Error::InvalidPacket)?;
+    for (start, length) in ranges {
+        let start = *start as usize;
+        push_source_symbols(&mut fec, data, start, start + length);
+    }
+    Ok(fec)
+}
+
+/// Push the source symbols of the FEC transport object covered by the range `start..end` of the object
+///
+/// Symbols partially covered by the range are skipped, except the last symbols of the object
+/// that are completed with the padding and the length of the object.
+fn push_source_symbols(fec: &mut ObjectDecoder<Vec<u8>>, object: &[u8], start: usize, end: usize) {
+    let oti = *fec.transmission_information();
+    let symbol_size = oti.symbol_size as usize;
+    let fec_length = oti.transfer_length as usize;
+    if object.len() + OBJECT_LENGTH_SIZE > fec_length {
+        return;
+    }
+
+    let last = match end == object.len() {
+        true => fec_length / symbol_size,
+        false => end / symbol_size,
+    };
+
+    let object_length = (object.len() as u32).to_be_bytes();
+    let mut symbol = vec![0u8; symbol_size];
+    for index in start.div_ceil(symbol_size)..last {
+        let offset = index * symbol_size;
+        symbol.fill(0);
+        if offset < object.len() {
+            let source = &object[offset..(offset + symbol_size).min(object.len())];
+            symbol[..source.len()].copy_from_slice(source);
+        }
+        for (i, byte) in object_length.iter().enumerate() {
+            let position = fec_length - OBJECT_LENGTH_SIZE + i;
+            if (offset..offset + symbol_size).contains(&position) {
+                symbol[position - offset] = *byte;
+            }
+        }
+
+        if let Some((sbn, esi)) = oti.symbol_position(index as u64) {
+            fec.push_encoding_symbol(sbn, esi, &symbol);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{RepairFlow, RouteDecoder, RouteEncoder, SourcePacket};
+    use crate::alc::LctHeader;
+    use crate::error::Error;
+    use alloc::vec::Vec;
+
+    fn object(length: usize) -> Vec<u8> {
+        (0..length).map(|i| (i * 7 + i / 256) as u8).collect()
+    }
+
+    #[test]
+    fn test_source_packet_round_trip() {
+        let mut header = LctHeader::new(1, 2);
+        header.codepoint = super::CODEPOINT_MEDIA_SEGMENT_FILE;
+        header.transfer_object_length = Some(1000);
+        let payload = [1u8, 2, 3];
+        let packet = SourcePacket::new(header, 997, &payload);
+        let data = packet.to_bytes().unwrap();
+        assert!(SourcePacket::parse(&data).unwrap() == packet);
+        assert!(SourcePacket::parse(&data[..data.len() - 5]) == Err(Error::InvalidPacket));
+    }
+
+    #[test]
+    fn test_repair_flow_parameters() {
+        assert!(RepairFlow::new(1, 0, 10, 10).is_none());
+        assert!(RepairFlow::new(1, 16, 0, 10).is_none());
+        assert!(RepairFlow::new(1, 16, 1, 10).is_some());
+        assert!(RepairFlow::new(1, 16, 8192, 700).is_some());
+        assert!(RepairFlow::new(1, 16, 8192, 701).is_none());
+        assert!(RepairFlow::new(1, 16, 8193, 10).is_none());
+
+        let repair = RepairFlow::new(1, 16, 10, 10).unwrap();
+        assert!(repair.fec_transport_object_length(0) == 16);
+        assert!(repair.fec_transport_object_length(12) == 16);
+        assert!(repair.fec_transport_object_length(13) == 32);
+        assert!(repair.fec_transport_object_length(60) == 64);
+        assert!(repair.fec_transport_object_length(61) == 80);
+
+        let encoder = RouteEncoder::new(1, 0, 24).unwrap();
+        assert!(encoder.with_repair_flow(repair).is_none());
+    }
+
+    #[test]
+    fn test_recover_from_repair_flow() {
+        crate::tests::init();
+
+        // The length of the object falls at the end of the last symbol, or in an extra symbol,
+        // source blocks of less than 4 symbols are padded by the source block encoder and decoder
+        for (max, length) in [1, 3, 8]
+            .into_iter()
+            .flat_map(|max| [0, 1, 12, 13, 16, 60, 61, 100, 171].map(|length| (max, length)))
+        {
+            let repair = RepairFlow::new(2, 16, max, 300).unwrap();
+            let object = object(length);
+            let encoder = RouteEncoder::new(1, super::CODEPOINT_NRT_FILE, 32)
+                .unwrap()
+                .with_repair_flow(repair)
+                .unwrap();
+
+            let mut decoder = RouteDecoder::new(1).with_repair_flow(repair);
+            for packet in encoder.repair_packets(5, &object).unwrap() {
+                decoder.push(&packet).unwrap();
+            }
+            let objects = decoder.take_objects();
+            assert!(objects.len() == 1);
+            assert!(objects[0].toi == 5);
+            assert!(objects[0].codepoint.is_none());
+            assert!(objects[0].data == object);
+
+            // Late source packets are ignored
+            for packet in encoder.source_packets(5, &object).unwrap() {
+                decoder.push(&packet).unwrap();
+            }
+            assert!(decoder.take_objects().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_source_and_repair_symbols() {
+        crate::tests::init();
+
+        let repair = RepairFlow::new(2, 16, 8, 200).unwrap();
+        for length in [1, 12, 13, 16, 60, 61, 100, 171] {
+            let object = object(length);
+            let encoder = RouteEncoder::new(1, super::CODEPOINT_NRT_FILE, 16)
+                .unwrap()
+                .with_repair_flow(repair)
+                .unwrap();
+            let source_packets = encoder.source_packets(5, &object).unwrap();
+            let repair_packets = encoder.repair_packets(5, &object).unwrap();
+
+            // The last source packet is lost, the tail symbol is recovered with the repair symbols
+            let mut decoder = RouteDecoder::new(1).with_repair_flow(repair);
+            for packet in &repair_packets {
+                decoder.push(packet).unwrap();
+            }
+            for packet in &source_packets[..source_packets.len() - 1] {
+                decoder.push(packet).unwrap();
+            }
+            let objects = decoder.take_objects();
+            assert!(objects.len() == 1);
+            assert!(objects[0].data == object);
+        }
+    }
+
+    #[test]
+    fn test_max_object_size() {
+        crate::tests::init();
+
+        let repair = RepairFlow::new(2, 16, 8, 200).unwrap();
+        let mut decoder = RouteDecoder::new(1)
+            .with_repair_flow(repair)
+            .with_max_object_size(100);
+
+        // A forged transfer object length is rejected before the object is allocated
+        for length in [101, u32::MAX as u64] {
+            let mut header = LctHeader::new(1, 2);
+            header.transfer_object_length = Some(length);
+            let packet = SourcePacket::new(header, 0, &[1, 2]);
+            assert!(decoder.push(&packet.to_bytes().unwrap()) == Err(Error::InvalidPacket));
+
+            let mut header = LctHeader::new(2, 2);
+            header.transfer_object_length = Some(length + 16);
+            let packet = crate::alc::AlcPacket::new(header, 0, 8, &[0; 16]);
+            assert!(decoder.push(&packet.to_bytes().unwrap()) == Err(Error::InvalidPacket));
+        }
+        let mut decoder = RouteDecoder::new(1);
+        let mut header = LctHeader::new(1, 2);
+        header.transfer_object_length = Some(super::DEFAULT_MAX_OBJECT_SIZE + 1);
+        let packet = SourcePacket::new(header, 0, &[1, 2]);
+        assert!(decoder.push(&packet.to_bytes().unwrap()) == Err(Error::InvalidPacket));
+
+        // Objects up to the max size are delivered, from the source flow or the repair flow
+        let object = object(100);
+        let encoder = RouteEncoder::new(1, super::CODEPOINT_NRT_FILE, 16)
+            .unwrap()
+            .with_repair_flow(repair)
+            .unwrap();
+        let mut decoder = RouteDecoder::new(1)
+            .with_repair_flow(repair)
+            .with_max_object_size(100);
+        for packet in encoder.source_packets(5, &object).unwrap() {
+            decoder.push(&packet).unwrap();
+        }
+        for packet in encoder.repair_packets(6, &object).unwrap() {
+            decoder.push(&packet).unwrap();
+        }
+        let objects = decoder.take_objects();
+        assert!(objects.len() == 2);
+        assert!(objects.iter().all(|delivered| delivered.data == object));
+    }
+
+    #[test]
+    fn test_deliver_without_fec_decoder() {
+        crate::tests::init();
+
+        // 1-byte symbols in source blocks of 1 symbol: the FEC transport object has too many source blocks
+        let repair = RepairFlow::new(2, 1, 1, 10).unwrap();
+        let object = object(70000);
+        let encoder = RouteEncoder::new(1, super::CODEPOINT_NRT_FILE, 1000)
+            .unwrap()
+            .with_repair_flow(repair)
+            .unwrap();
+        assert!(encoder.repair_packets(5, &object) == Err(Error::InvalidPacket));
+
+        let mut decoder = RouteDecoder::new(1).with_repair_flow(repair);
+        for packet in encoder.source_packets(5, &object).unwrap() {
+            decoder.push(&packet).unwrap();
+        }
+        let objects = decoder.take_objects();
+        assert!(objects.len() == 1);
+        assert!(objects[0].data == object);
+    }
+
+    #[test]
+    fn test_invalid_source_packets() {
+        let mut decoder = RouteDecoder::new(1);
+
+        // Transfer object length is missing
+        let packet = SourcePacket::new(LctHeader::new(1, 2), 0, &[1, 2]);
+        assert!(decoder.push(&packet.to_bytes().unwrap()) == Err(Error::InvalidPacket));
+
+        // Range beyond the end of the object
+        let mut header = LctHeader::new(1, 2);
+        header.transfer_object_length = Some(10);
+        let packet = SourcePacket::new(header, 9, &[1, 2]);
+        assert!(decoder.push(&packet.to_bytes().unwrap()) == Err(Error::InvalidPacket));
+
+        // Another session is ignored
+        let packet = SourcePacket::new(LctHeader::new(3, 2), 0, &[1, 2]);
+        assert!(decoder.push(&packet.to_bytes().unwrap()).is_ok());
+    }
+}
@@ -0,0 +1,179 @@
+use crate::common;
+use alloc::vec;
+use alloc::vec::Vec;
+
+///
+/// Row of coefficients of the decoding matrix over GF(2)
+///
+/// A row starts as a sorted list of indices and switches to a bitset
+/// when it becomes dense, at the point where both take the same amount of memory.
+/// Dense rows are XOR-ed a 64-bit word at a time.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Row {
+    /// Sorted indices of the non-zero coefficients
+    Sparse(Vec<u32>),
+    /// Bitset of the coefficients and number of non-zero coefficients
+    Dense(Vec<u64>, usize),
+}
+
+impl Row {
+    /// Create an empty row
+    pub fn new() -> Self {
+        Row::Sparse(Vec::new())
+    }
+
+    /// Create a row from sorted indices, `width` is the number of columns of the matrix
+    pub fn from_indices(indices: Vec<u32>, width: usize) -> Self {
+        let mut row = Row::Sparse(indices);
+        row.densify_if_needed(width);
+        row
+    }
+
+    /// Return true if all the coefficients are zero
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the number of non-zero coefficients
+    pub fn len(&self) -> usize {
+        match self {
+            Row::Sparse(indices) => indices.len(),
+            Row::Dense(_, count) => *count,
+        }
+    }
+
+    /// Return the index of the leftmost non-zero coefficient
+    pub fn first(&self) -> Option<u32> {
+        match self {
+            Row::Sparse(indices) => indices.first().copied(),
+            Row::Dense(words, _) => words
+                .iter()
+                .position(|word| *word != 0)
+                .map(|w| (w * 64) as u32 + words[w].trailing_zeros()),
+        }
+    }
+
+    /// Return true if the coefficient `i` is non-zero
+    pub fn contains(&self, i: u32) -> bool {
+        match self {
+            Row::Sparse(indices) => indices.binary_search(&i).is_ok(),
+            Row::Dense(words, _) => (words[i as usize / 64] >> (i % 64)) & 1 == 1,
+        }
+    }
+
+    /// Return the indices of the non-zero coefficients, in increasing order
+    #[cfg(test)]
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        let (sparse, dense) = match self {
+            Row::Sparse(indices) => (Some(indices.iter().copied()), None),
+            Row::Dense(words, _) => (
+                None,
+                Some((0..words.len() * 64).filter_map(move |i| {
+                    ((words[i / 64] >> (i % 64)) & 1 == 1).then_some(i as u32)
+                })),
+            ),
+        };
+        sparse
+            .into_iter()
+            .flatten()
+            .chain(dense.into_iter().flatten())
+    }
+
+    /// Only keep the leftmost non-zero coefficient
+    pub fn truncate_to_first(&mut self) {
+        if let Some(first) = self.first() {
+            *self = Row::Sparse(vec![first]);
+        }
+    }
+
+    /// Add `other` to the row (XOR), `width` is the number of columns of the matrix
+    pub fn xor(&mut self, other: &Row, width: usize) {
+        match (&mut *self, other) {
+            (Row::Sparse(indices), Row::Sparse(other)) => {
+                common::symmetric_difference(indices, other);
+                self.densify_if_needed(width);
+            }
+            (Row::Dense(words, count), Row::Sparse(other)) => {
+                for i in other {
+                    words[*i as usize / 64] ^= 1 << (i % 64);
+                }
+                *count = words.iter().map(|word| word.count_ones() as usize).sum();
+            }
+            (Row::Sparse(_), Row::Dense(..)) => {
+                self.densify(width);
+                self.xor(other, width);
+            }
+            (Row::Dense(words, count), Row::Dense(other, _)) => {
+                for (word, other) in words.iter_mut().zip(other) {
+                    *word ^= other;
+                }
+                *count = words.iter().map(|word| word.count_ones() as usize).sum();
+            }
+        }
+    }
+
+    fn densify_if_needed(&mut self, width: usize) {
+        // A sparse row takes 32 bits per coefficient
+        if let Row::Sparse(indices) = self {
+            if indices.len() * 32 >= width {
+                self.densify(width);
+            }
+        }
+    }
+
+    fn densify(&mut self, width: usize) {
+        if let Row::Sparse(indices) = self {
+            let mut words = vec![0u64; width.div_ceil(64)];
+            for i in indices.iter() {
+                words[*i as usize / 64] |= 1 << (i % 64);
+            }
+            *self = Row::Dense(words, indices.len());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::Row;
+
+    #[test]
+    fn test_sparse_row() {
+        let mut row = Row::from_indices(vec![1, 5, 9], 1000);
+        row.xor(&Row::from_indices(vec![0, 5, 10], 1000), 1000);
+        assert!(matches!(row, Row::Sparse(_)));
+        assert!(row.iter().collect::<Vec<_>>() == vec![0, 1, 9, 10]);
+        assert!(row.first() == Some(0));
+        assert!(row.contains(9) && !row.contains(5));
+    }
+
+    #[test]
+    fn test_dense_row() {
+        let width = 90;
+        let mut row = Row::from_indices(vec![3, 64, 89], width);
+        assert!(matches!(row, Row::Dense(..)));
+        assert!(row.len() == 3);
+
+        // Dense ^ Sparse
+        row.xor(&Row::Sparse(vec![3, 70]), width);
+        assert!(row.iter().collect::<Vec<_>>() == vec![64, 70, 89]);
+        assert!(row.first() == Some(64));
+
+        // Sparse ^ Dense
+        let mut sparse = Row::Sparse(vec![64]);
+        sparse.xor(&row, width);
+        assert!(matches!(sparse, Row::Dense(..)));
+        assert!(sparse.iter().collect::<Vec<_>>() == vec![70, 89]);
+
+        // Dense ^ Dense
+        sparse.xor(&row, width);
+        assert!(sparse.iter().collect::<Vec<_>>() == vec![64]);
+        assert!(sparse.contains(64) && !sparse.contains(89));
+
+        sparse.truncate_to_first();
+        assert!(sparse == Row::Sparse(vec![64]));
+        sparse.xor(&Row::Sparse(vec![64]), width);
+        assert!(sparse.is_empty() && sparse.first().is_none());
+    }
+}
@@ -0,0 +1,615 @@
+//!
+//! RTP payload format for Raptor FEC
+//!
+//! Protection of RTP flows with the FEC Framework (RFC 6363), the Raptor FEC scheme for arbitrary packet flows (RFC 6681)
+//! and the RTP payload format of the repair packets (RFC 6682).
+//!
+//! Every source RTP packet is wrapped into an Application Data Unit Information (ADUI),
+//! made of the flow ID, the length of the packet and the packet padded to a multiple of the symbol size.
+//! The ADUIs are concatenated into a source block, encoded with a [`SourceBlockEncoder`].
+//!
+//! * Source packets are sent unchanged, followed by the Source FEC Payload ID
+//! * Repair packets are RTP packets of the repair flow, carrying the Repair FEC Payload ID and a repair symbol
+//!
+//! ```text
+//! Source FEC Payload ID                    Repair FEC Payload ID
+//! +-------------------+-----------------+  +-------------------+-----------------+
+//! | Source Block Nb   | Encoding Sym ID |  | Source Block Nb   | Encoding Sym ID |
+//! +-------------------+-----------------+  +-------------------+-----------------+
+//!                                          | Source Block Len  |
+//!                                          +-------------------+
+//! ```
+//!
+
+use crate::error::Error;
+use crate::{SourceBlockDecoder, SourceBlockEncoder};
+use alloc::collections::{BTreeSet, VecDeque};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// RTP version
+const RTP_VERSION: u8 = 2;
+
+/// Size of the fixed RTP header
+const RTP_HEADER_SIZE: usize = 12;
+
+/// Size of the Source FEC Payload ID (SBN and ESI)
+const SOURCE_FEC_PAYLOAD_ID_SIZE: usize = 4;
+
+/// Size of the Repair FEC Payload ID (SBN, ESI and source block length)
+const REPAIR_FEC_PAYLOAD_ID_SIZE: usize = 6;
+
+/// Size of the flow ID and the length of an ADUI
+const ADUI_HEADER_SIZE: usize = 3;
+
+/// Max number of source blocks kept by the decoder waiting for repair packets
+const MAX_PENDING_BLOCKS: usize = 8;
+
+///
+/// Fixed RTP header, RFC 3550 section 5.1
+///
+/// CSRC, header extensions and padding are skipped by the parser and never written.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RtpHeader {
+    /// Marker bit (M)
+    pub marker: bool,
+    /// Payload type (PT)
+    pub payload_type: u8,
+    /// Sequence number
+    pub sequence_number: u16,
+    /// Timestamp
+    pub timestamp: u32,
+    /// Synchronization source identifier (SSRC)
+    pub ssrc: u32,
+}
+
+impl RtpHeader {
+    /// Serialize the header at the end of `output`
+    pub fn write(&self, output: &mut Vec<u8>) {
+        output.push(RTP_VERSION << 6);
+        output.push(((self.marker as u8) << 7) | (self.payload_type & 0x7F));
+        output.extend_from_slice(&self.sequence_number.to_be_bytes());
+        output.extend_from_slice(&self.timestamp.to_be_bytes());
+        output.extend_from_slice(&self.ssrc.to_be_bytes());
+    }
+
+    /// Parse the header of an RTP packet
+    ///
+    /// # Returns
+    ///
+    /// * `Err(Error::InvalidPacket)` if the packet is not an RTP packet
+    /// * `Ok((RtpHeader, usize))` the header and the offset of the payload
+    pub fn parse(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.len() < RTP_HEADER_SIZE || data[0] >> 6 != RTP_VERSION {
+            return Err(Error::InvalidPacket);
+        }
+
+        let csrc_count = (data[0] & 0x0F) as usize;
+        let mut offset = RTP_HEADER_SIZE + 4 * csrc_count;
+        if data[0] & 0x10 != 0 {
+            // Header extension: profile and length in 32-bit words
+            let length = data
+                .get(offset + 2..offset + 4)
+                .ok_or(Error::InvalidPacket)?;
+            offset += 4 + 4 * u16::from_be_bytes([length[0], length[1]]) as usize;
+        }
+        if offset > data.len() {
+            return Err(Error::InvalidPacket);
+        }
+
+        let header = RtpHeader {
+            marker: data[1] >> 7 == 1,
+            payload_type: data[1] & 0x7F,
+            sequence_number: u16::from_be_bytes([data[2], data[3]]),
+            timestamp: u32::from_be_bytes(data[4..8].try_into().unwrap()),
+            ssrc: u32::from_be_bytes(data[8..12].try_into().unwrap()),
+        };
+        Ok((header, offset))
+    }
+}
+
+/// Return the flow ID of the source flow `ssrc`
+fn flow_id(flows: &[u32], ssrc: u32) -> Result<u8, Error> {
+    flows
+        .iter()
+        .position(|flow| *flow == ssrc)
+        .map(|flow| flow as u8)
+        .ok_or(Error::InvalidPacket)
+}
+
+/// Register a source flow, return its flow ID
+fn add_flow(flows: &mut Vec<u32>, ssrc: u32) -> Option<u8> {
+    if let Ok(flow) = flow_id(flows, ssrc) {
+        return Some(flow);
+    }
+    if flows.len() > u8::MAX as usize {
+        return None;
+    }
+    flows.push(ssrc);
+    Some((flows.len() - 1) as u8)
+}
+
+///
+/// FEC sender of RTP flows
+///
+/// Source packets are appended to the current source block as they are protected.
+/// When the source block is full, or when [`RtpFecEncoder::finish_block`] is called,
+/// the repair packets of the block are generated.
+///
+pub struct RtpFecEncoder {
+    flows: Vec<u32>,
+    repair: RtpHeader,
+    symbol_size: usize,
+    max_source_symbols: usize,
+    nb_repair: u32,
+    sbn: u16,
+    source_block: Vec<u8>,
+    repair_packets: Vec<Vec<u8>>,
+}
+
+impl RtpFecEncoder {
+    /// Create an RTP FEC sender
+    ///
+    /// # Parameters
+    ///
+    /// * `repair_payload_type`: Payload type of the repair flow.
+    /// * `repair_ssrc`: SSRC of the repair flow.
+    /// * `symbol_size`: Size of a symbol in bytes (T).
+    /// * `max_source_symbols`: Max number of source symbols of a source block, at most [`crate::MAX_SOURCE_SYMBOLS_PER_BLOCK`].
+    /// * `nb_repair`: Number of repair packets generated per source block.
+    ///
+    /// # Returns
+    ///
+    /// * `None` if the parameters are out of range or the encoding symbols of a source block cannot be identified by a 16-bit ESI
+    /// * `Some(RtpFecEncoder)` otherwise
+    pub fn new(
+        repair_payload_type: u8,
+        repair_ssrc: u32,
+        symbol_size: u16,
+        max_source_symbols: u32,
+        nb_repair: u32,
+    ) -> Option<Self> {
+        if symbol_size == 0
+            || max_source_symbols == 0
+            || max_source_symbols > crate::MAX_SOURCE_SYMBOLS_PER_BLOCK
+            || max_source_symbols as u64 + nb_repair as u64 > u16::MAX as u64 + 1
+        {
+            return None;
+        }
+
+        Some(RtpFecEncoder {
+            flows: Vec::new(),
+            repair: RtpHeader {
+                payload_type: repair_payload_type,
+                ssrc: repair_ssrc,
+                ..Default::default()
+            },
+            symbol_size: symbol_size as usize,
+            max_source_symbols: max_source_symbols as usize,
+            nb_repair,
+            sbn: 0,
+            source_block: Vec::new(),
+            repair_packets: Vec::new(),
+        })
+    }
+
+    /// Protect the RTP flow `ssrc`
+    ///
+    /// Flow IDs are assigned in registration order, the receiver must register the same flows in the same order.
+    ///
+    /// # Returns
+    ///
+    /// * `None` if 256 flows are already protected
+    /// * `Some(u8)` the flow ID
+    pub fn add_source_flow(&mut self, ssrc: u32) -> Option<u8> {
+        add_flow(&mut self.flows, ssrc)
+    }
+
+    /// Add an RTP packet to the current source block
+    ///
+    /// # Returns
+    ///
+    /// * `Err(Error::InvalidPacket)` if the packet is not an RTP packet of a protected flow or does not fit in a source block
+    /// * `Ok(Vec<u8>)` the source packet to send, followed by its Source FEC Payload ID
+    pub fn protect(&mut self, rtp_packet: &[u8]) -> Result<Vec<u8>, Error> {
+        let (header, _) = RtpHeader::parse(rtp_packet)?;
+        let flow = flow_id(&self.flows, header.ssrc)?;
+        let adui_length = ADUI_HEADER_SIZE + rtp_packet.len();
+        let nb_symbols = adui_length.div_ceil(self.symbol_size);
+        if rtp_packet.len() > u16::MAX as usize || nb_symbols > self.max_source_symbols {
+            return Err(Error::InvalidPacket);
+        }
+
+        if self.nb_source_symbols() + nb_symbols > self.max_source_symbols {
+            self.finish_block();
+        }
+
+        let esi = self.nb_source_symbols() as u16;
+        self.source_block.push(flow);
+        self.source_block
+            .extend_from_slice(&(rtp_packet.len() as u16).to_be_bytes());
+        self.source_block.extend_from_slice(rtp_packet);
+        self.source_block
+            .resize((esi as usize + nb_symbols) * self.symbol_size, 0);
+        self.repair.timestamp = header.timestamp;
+
+        let mut source_packet = Vec::with_capacity(rtp_packet.len() + SOURCE_FEC_PAYLOAD_ID_SIZE);
+        source_packet.extend_from_slice(rtp_packet);
+        source_packet.extend_from_slice(&self.sbn.to_be_bytes());
+        source_packet.extend_from_slice(&esi.to_be_bytes());
+        Ok(source_packet)
+    }
+
+    /// Close the current source block and generate its repair packets
+    ///
+    /// The repair packets carry the timestamp of the last source packet of the block.
+    pub fn finish_block(&mut self) {
+        if self.source_block.is_empty() {
+            return;
+        }
+
+        let k = self.nb_source_symbols();
+        let mut encoder = SourceBlockEncoder::new(&self.source_block, k);
+        for esi in k as u32..k as u32 + self.nb_repair {
+            let mut symbol = encoder.fountain(esi);
+            symbol.resize(self.symbol_size, 0);
+
+            let mut packet =
+                Vec::with_capacity(RTP_HEADER_SIZE + REPAIR_FEC_PAYLOAD_ID_SIZE + self.symbol_size);
+            self.repair.write(&mut packet);
+            packet.extend_from_slice(&self.sbn.to_be_bytes());
+            packet.extend_from_slice(&(esi as u16).to_be_bytes());
+            packet.extend_from_slice(&(k as u16).to_be_bytes());
+            packet.extend_from_slice(&symbol);
+            self.repair_packets.push(packet);
+            self.repair.sequence_number = self.repair.sequence_number.wrapping_add(1);
+        }
+
+        self.sbn = self.sbn.wrapping_add(1);
+        self.source_block.clear();
+    }
+
+    /// Take the repair packets generated since the last call
+    pub fn take_repair_packets(&mut self) -> Vec<Vec<u8>> {
+        core::mem::take(&mut self.repair_packets)
+    }
+
+    fn nb_source_symbols(&self) -> usize {
+        self.source_block.len() / self.symbol_size
+    }
+}
+
+/// Source block being received
+struct PendingBlock {
+    sbn: u16,
+    /// Number of source symbols (K), known once a repair packet is received
+    nb_source_symbols: usize,
+    /// ESI of the first symbol of the source packets received
+    received_packets: BTreeSet<u16>,
+    /// Source symbols received before the source block length is known
+    source_symbols: Vec<(u32, Vec<u8>)>,
+    decoder: Option<SourceBlockDecoder>,
+    decoded: bool,
+}
+
+///
+/// FEC receiver of RTP flows
+///
+/// Source packets are forwarded to the application as they are received, and lost source packets
+/// are reconstructed as soon as enough repair packets of their source block are received.
+///
+pub struct RtpFecDecoder {
+    flows: Vec<u32>,
+    symbol_size: usize,
+    blocks: VecDeque<PendingBlock>,
+    recovered: Vec<Vec<u8>>,
+}
+
+impl RtpFecDecoder {
+    /// Create an RTP FEC receiver, `symbol_size` is the size of a symbol in bytes (T)
+    ///
+    /// # Returns
+    ///
+    /// * `None` if `symbol_size` is 0
+    /// * `Some(RtpFecDecoder)` otherwise
+    pub fn new(symbol_size: u16) -> Option<Self> {
+        if symbol_size == 0 {
+            return None;
+        }
+
+        Some(RtpFecDecoder {
+            flows: Vec::new(),
+            symbol_size: symbol_size as usize,
+            blocks: VecDeque::new(),
+            recovered: Vec::new(),
+        })
+    }
+
+    /// Register the RTP flow `ssrc`, see [`RtpFecEncoder::add_source_flow`]
+    pub fn add_source_flow(&mut self, ssrc: u32) -> Option<u8> {
+        add_flow(&mut self.flows, ssrc)
+    }
+
+    /// Receive a source packet
+    ///
+    /// # Returns
+    ///
+    /// * `Err(Error::InvalidPacket)` if the packet is not an RTP packet of a protected flow followed by a Source FEC Payload ID
+    /// * `Ok(&[u8])` the RTP packet, without the Source FEC Payload ID
+    pub fn receive_source<'a>(&mut self, source_packet: &'a [u8]) -> Result<&'a [u8], Error> {
+        let length = source_packet
+            .len()
+            .checked_sub(SOURCE_FEC_PAYLOAD_ID_SIZE)
+            .ok_or(Error::InvalidPacket)?;
+        let (rtp_packet, fec_payload_id) = source_packet.split_at(length);
+        let (header, _) = RtpHeader::parse(rtp_packet)?;
+        let flow = flow_id(&self.flows, header.ssrc)?;
+        let sbn = u16::from_be_bytes([fec_payload_id[0], fec_payload_id[1]]);
+        let esi = u16::from_be_bytes([fec_payload_id[2], fec_payload_id[3]]);
+
+        let mut adui = Vec::with_capacity(ADUI_HEADER_SIZE + rtp_packet.len());
+        adui.push(flow);
+        adui.extend_from_slice(&(rtp_packet.len() as u16).to_be_bytes());
+        adui.extend_from_slice(rtp_packet);
+        adui.resize(adui.len().div_ceil(self.symbol_size) * self.symbol_size, 0);
+
+        let index = self.block_index(sbn);
+        let block = &mut self.blocks[index];
+        if block.decoded || !block.received_packets.insert(esi) {
+            return Ok(rtp_packet);
+        }
+
+        for (i, symbol) in adui.chunks(self.symbol_size).enumerate() {
+            let esi = esi as u32 + i as u32;
+            match &mut block.decoder {
+                Some(decoder) => decoder.push_encoding_symbol(symbol, esi),
+                None => block.source_symbols.push((esi, symbol.to_vec())),
+            }
+        }
+        self.try_decode(index);
+        Ok(rtp_packet)
+    }
+
+    /// Receive a repair packet
+    ///
+    /// # Returns
+    ///
+    /// * `Err(Error::InvalidPacket)` if the packet is not an RTP packet followed by a Repair FEC Payload ID,
+    ///   or if its source block length differs from the one of the previous repair packets of the block
+    /// * `Ok(())` otherwise
+    pub fn receive_repair(&mut self, repair_packet: &[u8]) -> Result<(), Error> {
+        let (_, offset) = RtpHeader::parse(repair_packet)?;
+        let fec_payload_id = repair_packet
+            .get(offset..offset + REPAIR_FEC_PAYLOAD_ID_SIZE)
+            .ok_or(Error::InvalidPacket)?;
+        let sbn = u16::from_be_bytes([fec_payload_id[0], fec_payload_id[1]]);
+        let esi = u16::from_be_bytes([fec_payload_id[2], fec_payload_id[3]]);
+        let k = u16::from_be_bytes([fec_payload_id[4], fec_payload_id[5]]) as usize;
+        if k == 0 || k > crate::MAX_SOURCE_SYMBOLS_PER_BLOCK as usize {
+            return Err(Error::InvalidPacket);
+        }
+        let symbol = &repair_packet[offset + REPAIR_FEC_PAYLOAD_ID_SIZE..];
+        if symbol.len() != self.symbol_size {
+            return Err(Error::InvalidPacket);
+        }
+
+        let index = self.block_index(sbn);
+        let block = &mut self.blocks[index];
+        if block.decoded {
+            return Ok(());
+        }
+        if block.nb_source_symbols != 0 && block.nb_source_symbols != k {
+            return Err(Error::InvalidPacket);
+        }
+
+        block.nb_source_symbols = k;
+        let decoder = block.decoder.get_or_insert_with(|| {
+            let mut decoder = SourceBlockDecoder::new(k);
+            for (esi, symbol) in block.source_symbols.drain(..) {
+                decoder.push_owned(symbol, esi);
+            }
+            decoder
+        });
+        decoder.push_encoding_symbol(symbol, esi as u32);
+        self.try_decode(index);
+        Ok(())
+    }
+
+    /// Take the RTP packets reconstructed since the last call
+    pub fn take_recovered_packets(&mut self) -> Vec<Vec<u8>> {
+        core::mem::take(&mut self.recovered)
+    }
+
+    /// Return the index of the source block `sbn`, the oldest block is dropped when too many blocks are pending
+    fn block_index(&mut self, sbn: u16) -> usize {
+        if let Some(index) = self.blocks.iter().position(|block| block.sbn == sbn) {
+            return index;
+        }
+
+        if self.blocks.len() == MAX_PENDING_BLOCKS {
+            self.blocks.pop_front();
+        }
+        self.blocks.push_back(PendingBlock {
+            sbn,
+            nb_source_symbols: 0,
+            received_packets: BTreeSet::new(),
+            source_symbols: Vec::new(),
+            decoder: None,
+            decoded: false,
+        });
+        self.blocks.len() - 1
+    }
+
+    /// Decode the source block when possible and reconstruct its lost source packets
+    fn try_decode(&mut self, index: usize) {
+        let block = &mut self.blocks[index];
+        let Some(decoder) = block.decoder.as_mut() else {
+            return;
+        };
+        if !decoder.fully_specified() {
+            return;
+        }
+
+        let mut source_block = vec![0u8; block.nb_source_symbols * self.symbol_size];
+        if !decoder.decode_into(&mut source_block) {
+            return;
+        }
+        block.decoded = true;
+        block.decoder = None;
+
+        // Walk the ADUIs, every ADUI starts on a symbol boundary
+        let mut esi = 0;
+        while let Some(adui) = source_block.get(esi * self.symbol_size..) {
+            if adui.len() < ADUI_HEADER_SIZE {
+                break;
+            }
+            let flow = adui[0] as usize;
+            let length = u16::from_be_bytes([adui[1], adui[2]]) as usize;
+            let rtp_packet = match adui.get(ADUI_HEADER_SIZE..ADUI_HEADER_SIZE + length) {
+                Some(rtp_packet) if length > 0 => rtp_packet,
+                _ => {
+                    #[cfg(feature = "log")]
+                    log::warn!("Invalid ADUI in source block {}", block.sbn);
+                    break;
+                }
+            };
+
+            if flow < self.flows.len() && !block.received_packets.contains(&(esi as u16)) {
+                self.recovered.push(rtp_packet.to_vec());
+            }
+            esi += (ADUI_HEADER_SIZE + length).div_ceil(self.symbol_size);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::RtpHeader;
+    use crate::error::Error;
+    use alloc::vec::Vec;
+
+    fn rtp_packet(ssrc: u32, sequence_number: u16, payload_size: usize) -> Vec<u8> {
+        let header = RtpHeader {
+            marker: false,
+            payload_type: 96,
+            sequence_number,
+            timestamp: sequence_number as u32 * 3000,
+            ssrc,
+        };
+        let mut packet = Vec::new();
+        header.write(&mut packet);
+        packet.extend((0..payload_size).map(|i| (i as u16 + sequence_number) as u8));
+        packet
+    }
+
+    #[test]
+    fn test_rtp_header() {
+        let header = RtpHeader {
+            marker: true,
+            payload_type: 100,
+            sequence_number: 0xFFFE,
+            timestamp: 0xDEADBEEF,
+            ssrc: 0x12345678,
+        };
+        let mut packet = Vec::new();
+        header.write(&mut packet);
+        assert!(RtpHeader::parse(&packet).unwrap() == (header, 12));
+
+        // 2 CSRC and a header extension of 1 word
+        let mut packet = Vec::new();
+        header.write(&mut packet);
+        packet[0] |= 0x10 | 2;
+        packet.extend_from_slice(&[0; 8]);
+        packet.extend_from_slice(&[0xBE, 0xDE, 0, 1, 0, 0, 0, 0]);
+        assert!(RtpHeader::parse(&packet).unwrap() == (header, 28));
+        assert!(RtpHeader::parse(&packet[..26]).err() == Some(Error::InvalidPacket));
+
+        packet[0] = 1 << 6;
+        assert!(RtpHeader::parse(&packet).err() == Some(Error::InvalidPacket));
+        assert!(RtpHeader::parse(&packet[..11]).err() == Some(Error::InvalidPacket));
+    }
+
+    #[test]
+    fn test_rtp_fec_recovery() {
+        crate::tests::init();
+
+        let mut encoder = super::RtpFecEncoder::new(97, 0xFEC, 64, 16, 4).unwrap();
+        let mut decoder = super::RtpFecDecoder::new(64).unwrap();
+        assert!(encoder.add_source_flow(0xAAAA) == Some(0));
+        assert!(decoder.add_source_flow(0xAAAA) == Some(0));
+
+        let packets: Vec<Vec<u8>> = (0..5).map(|seq| rtp_packet(0xAAAA, seq, 150)).collect();
+        let mut source_packets = Vec::new();
+        for packet in &packets {
+            source_packets.push(encoder.protect(packet).unwrap());
+        }
+        encoder.finish_block();
+        let repair_packets = encoder.take_repair_packets();
+        assert!(repair_packets.len() == 4);
+
+        // Unknown flow
+        let unknown = rtp_packet(0xBBBB, 0, 10);
+        assert!(encoder.protect(&unknown).err() == Some(Error::InvalidPacket));
+
+        for (i, source_packet) in source_packets.iter().enumerate() {
+            if i != 2 {
+                assert!(decoder.receive_source(source_packet).unwrap() == packets[i]);
+            }
+        }
+        for repair_packet in &repair_packets {
+            decoder.receive_repair(repair_packet).unwrap();
+        }
+        assert!(decoder.take_recovered_packets() == [packets[2].clone()]);
+
+        // A single packet of 1 symbol, the repair symbols start at ESI 1
+        let mut encoder = super::RtpFecEncoder::new(97, 0xFEC, 64, 16, 8).unwrap();
+        let mut decoder = super::RtpFecDecoder::new(64).unwrap();
+        encoder.add_source_flow(0xAAAA).unwrap();
+        decoder.add_source_flow(0xAAAA).unwrap();
+        let packet = rtp_packet(0xAAAA, 5, 20);
+        encoder.protect(&packet).unwrap();
+        encoder.finish_block();
+        for (i, repair_packet) in encoder.take_repair_packets().iter().enumerate() {
+            assert!(repair_packet[14..16] == [0, 1 + i as u8]);
+            assert!(repair_packet[16..18] == [0, 1]);
+            decoder.receive_repair(repair_packet).unwrap();
+        }
+        assert!(decoder.take_recovered_packets() == [packet]);
+    }
+
+    #[test]
+    fn test_rtp_fec_parameters() {
+        crate::tests::init();
+
+        assert!(super::RtpFecEncoder::new(97, 0xFEC, 0, 16, 4).is_none());
+        assert!(super::RtpFecEncoder::new(97, 0xFEC, 64, 0, 4).is_none());
+        assert!(super::RtpFecEncoder::new(97, 0xFEC, 64, 8193, 4).is_none());
+        assert!(super::RtpFecEncoder::new(97, 0xFEC, 64, 8192, 57345).is_none());
+        assert!(super::RtpFecEncoder::new(97, 0xFEC, 64, 8192, 57344).is_some());
+        assert!(super::RtpFecDecoder::new(0).is_none());
+    }
+
+    #[test]
+    fn test_rtp_fec_source_block_length_mismatch() {
+        crate::tests::init();
+
+        let mut encoder = super::RtpFecEncoder::new(97, 0xFEC, 64, 16, 2).unwrap();
+        let mut decoder = super::RtpFecDecoder::new(64).unwrap();
+        encoder.add_source_flow(0xAAAA).unwrap();
+        decoder.add_source_flow(0xAAAA).unwrap();
+        for seq in 0..3 {
+            encoder.protect(&rtp_packet(0xAAAA, seq, 100)).unwrap();
+        }
+        encoder.finish_block();
+        let repair_packets = encoder.take_repair_packets();
+        decoder.receive_repair(&repair_packets[0]).unwrap();
+
+        // Same source block with another source block length
+        let mut forged = repair_packets[1].clone();
+        forged[17] += 1;
+        assert!(decoder.receive_repair(&forged).err() == Some(Error::InvalidPacket));
+        decoder.receive_repair(&repair_packets[1]).unwrap();
+    }
+}
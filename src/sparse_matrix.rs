@@ -1,4 +1,7 @@
 use crate::common;
+use crate::row::Row;
+use alloc::vec;
+use alloc::vec::Vec;
 
 /// Sparce Matrix
 ///
@@ -7,23 +10,30 @@ use crate::common;
 ///
 /// A^block = intermediate
 ///
+#[derive(Clone)]
 pub struct SparseMatrix {
     /// Indices of the source blocks which are xor-ed together
     /// | 0 0 1 1 |          [[ 2, 3],
     /// | 0 1 0 1 |           [ 1, 3 ],
     /// | 1 1 1 0 | -> coeff  [ 0, 1, 2],
     /// | 1 0 0 0 |           [ 0 ] ]
-    pub coeff: Vec<Vec<u32>>,
+    ///
+    /// Rows switch to a bitset when they become dense, see [`Row`]
+    pub coeff: Vec<Row>,
 
     /// Intermediate symbols
     pub intermediate: Vec<Vec<u8>>,
+
+    /// Number of non-empty rows
+    pub rank: usize,
 }
 
 impl SparseMatrix {
     pub fn new(l: usize) -> Self {
         SparseMatrix {
-            coeff: vec![Vec::new(); l],
-            intermediate: vec![vec![0; l]; l],
+            coeff: vec![Row::new(); l],
+            intermediate: vec![Vec::new(); l],
+            rank: 0,
         }
     }
 
@@ -45,35 +55,49 @@ impl SparseMatrix {
     ///
     /// Spreads decoding complexity during packets reception
     ///
-    pub fn add_equation(&mut self, components: Vec<u32>, b: Vec<u8>) {
-        let mut components = components;
+    /// # Returns
+    ///
+    /// * `Some(Vec<u8>)` if the equation is linearly dependent and has been discarded,
+    ///   the returned buffer can be reused
+    /// * `None` if the equation has been added to the matrix
+    ///
+    pub fn add_equation(&mut self, components: Vec<u32>, b: Vec<u8>) -> Option<Vec<u8>> {
+        let width = self.coeff.len();
+        let mut components = Row::from_indices(components, width);
         let mut b = b;
 
         // while EqOnes > 0 and G[s][s] = 1 do
-        while components.len() > 0 && self.coeff[components[0] as usize].len() > 0 {
+        while let Some(s) = components.first() {
+            let s = s as usize;
+            if self.coeff[s].is_empty() {
+                break;
+            }
             // s <- LeftmostOne
-            let s = components[0];
             // if EqOnes ≥ NumOnes[s] then
-            if components.len() >= self.coeff[s as usize].len() {
+            if components.len() >= self.coeff[s].len() {
                 // NewEq <- NewEq ^ G[s]
-                common::symmetric_difference(&mut components, &self.coeff[s as usize]);
+                components.xor(&self.coeff[s], width);
                 // NewY <- NewY ^ Y [s]
-                common::xor(&mut b, &self.intermediate[s as usize]);
+                common::xor(&mut b, &self.intermediate[s]);
             } else {
                 // Swap matrix row with the new row
-                std::mem::swap(&mut self.coeff[s as usize], &mut components);
-                std::mem::swap(&mut self.intermediate[s as usize], &mut b);
+                core::mem::swap(&mut self.coeff[s], &mut components);
+                core::mem::swap(&mut self.intermediate[s], &mut b);
             }
         }
 
         // if EqOnes > 0 then
-        if components.len() > 0 {
-            let s = components[0] as usize;
+        if let Some(s) = components.first() {
+            let s = s as usize;
             // G[s] <- NewEq
             self.coeff[s] = components;
             // Y [s] <- NewY
             self.intermediate[s] = b;
+            self.rank += 1;
+            return None;
         }
+
+        Some(b)
     }
 
     /// Check is the decode matrix is fully specified
@@ -87,15 +111,13 @@ impl SparseMatrix {
     pub fn reduce(&mut self) {
         for i in (0..self.coeff.len()).rev() {
             let (inter_j, inter_i) = self.intermediate.split_at_mut(i);
-            let first_coeff_i = self.coeff[i][0];
-            for j in 0..i {
-                for k in &self.coeff[j] {
-                    if *k == first_coeff_i {
-                        common::xor(&mut inter_j[j], &inter_i[0]);
-                    }
+            let first_coeff_i = self.coeff[i].first().unwrap();
+            for (coeff_j, inter_j) in self.coeff.iter().zip(inter_j.iter_mut()) {
+                if coeff_j.contains(first_coeff_i) {
+                    common::xor(inter_j, &inter_i[0]);
                 }
             }
-            self.coeff[i].resize(1, 0);
+            self.coeff[i].truncate_to_first();
         }
     }
 }
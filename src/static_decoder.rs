@@ -0,0 +1,377 @@
+use crate::common;
+use crate::partition::Partition;
+use crate::{MAX_SOURCE_SYMBOLS_PER_BLOCK, MIN_SOURCE_SYMBOLS_PER_BLOCK};
+use core::mem::MaybeUninit;
+
+/// Return the number of intermediate symbols (L) of a source block of `k` source symbols
+///
+/// Used to size the `L` parameter of a [`StaticDecoder`].
+pub const fn nb_intermediate_symbols(k: usize) -> usize {
+    common::intermediate_symbols(k as u32).0 as usize
+}
+
+/// Return the number of 32-bit words of a bit-packed coefficient row of `l` intermediate symbols
+///
+/// Used to size the `W` parameter of a [`StaticDecoder`].
+pub const fn nb_coefficient_words(l: usize) -> usize {
+    l.div_ceil(32)
+}
+
+///
+/// A source block decoder that does not allocate memory
+///
+/// The decoding matrix is stored inline: `L` bit-packed coefficient rows of `W` words
+/// and `L` intermediate symbols of `T` bytes.
+/// Encoding symbols are eliminated on the fly as they are received, like [`crate::SourceBlockDecoder`].
+///
+/// * `L`: Max number of intermediate symbols, see [`nb_intermediate_symbols`]
+/// * `T`: Max size of an encoding symbol in bytes
+/// * `W`: Number of words of a coefficient row, see [`nb_coefficient_words`]
+///
+/// The number of source symbols `K` is checked at compile time.
+/// Large decoders must be initialized in place with [`StaticDecoder::init`], see [`StaticDecoder::new`].
+///
+/// ```
+/// use raptor_code::{nb_coefficient_words, nb_intermediate_symbols, StaticDecoder};
+///
+/// const K: usize = 10;
+/// const L: usize = nb_intermediate_symbols(K);
+/// const W: usize = nb_coefficient_words(L);
+///
+/// let mut decoder = StaticDecoder::<L, 64, W>::new::<K>();
+/// assert!(!decoder.fully_specified());
+/// ```
+///
+/// A block that does not fit in the decoder fails to compile
+///
+/// ```compile_fail
+/// use raptor_code::StaticDecoder;
+///
+/// // 100 source symbols require 127 intermediate symbols
+/// let decoder = StaticDecoder::<100, 64, 4>::new::<100>();
+/// ```
+///
+/// Source blocks of less than 4 source symbols are not supported
+///
+/// ```compile_fail
+/// use raptor_code::StaticDecoder;
+///
+/// let decoder = StaticDecoder::<16, 64, 1>::new::<3>();
+/// ```
+///
+pub struct StaticDecoder<const L: usize, const T: usize, const W: usize> {
+    k: u32,
+    l: u32,
+    l_prime: u32,
+    coeff: [[u32; W]; L],
+    intermediate: [[u8; T]; L],
+    rank: usize,
+}
+
+impl<const L: usize, const T: usize, const W: usize> StaticDecoder<L, T, W> {
+    /// Create a decoder for a source block of `K` source symbols
+    ///
+    /// The decoder is built on the stack before it is returned, which overflows the stack of
+    /// a microcontroller for large blocks. Use [`StaticDecoder::init`] instead.
+    pub fn new<const K: usize>() -> Self {
+        let mut decoder = StaticDecoder {
+            k: 0,
+            l: 0,
+            l_prime: 0,
+            coeff: [[0; W]; L],
+            intermediate: [[0; T]; L],
+            rank: 0,
+        };
+        decoder.reset::<K>();
+        decoder
+    }
+
+    /// Initialize a decoder in place for a source block of `K` source symbols
+    ///
+    /// The decoder is never copied through the stack, so it can live in a `static`
+    /// or in a buffer larger than the stack.
+    ///
+    /// ```
+    /// use core::mem::MaybeUninit;
+    /// use raptor_code::{nb_coefficient_words, nb_intermediate_symbols, StaticDecoder};
+    ///
+    /// const K: usize = 1024;
+    /// const L: usize = nb_intermediate_symbols(K);
+    /// const W: usize = nb_coefficient_words(L);
+    ///
+    /// // 1.2 MB of decoding matrix, outside of the stack
+    /// static mut DECODER: MaybeUninit<StaticDecoder<L, 1024, W>> = MaybeUninit::uninit();
+    ///
+    /// // Safety: DECODER is only accessed here
+    /// let decoder = StaticDecoder::init::<K>(unsafe { &mut *core::ptr::addr_of_mut!(DECODER) });
+    /// assert!(decoder.nb_source_symbols() == K as u32);
+    /// ```
+    pub fn init<const K: usize>(decoder: &mut MaybeUninit<Self>) -> &mut Self {
+        // Safety: the fields are integers and arrays of integers, for which all-zero bytes is a valid value
+        let decoder = unsafe {
+            decoder.as_mut_ptr().write_bytes(0, 1);
+            decoder.assume_init_mut()
+        };
+        decoder.reset::<K>();
+        decoder
+    }
+
+    /// Reset the decoder in place for a new source block of `K` source symbols
+    pub fn reset<const K: usize>(&mut self) {
+        const {
+            assert!(
+                K >= MIN_SOURCE_SYMBOLS_PER_BLOCK as usize
+                    && K <= MAX_SOURCE_SYMBOLS_PER_BLOCK as usize,
+                "K is out of range"
+            );
+            assert!(
+                nb_intermediate_symbols(K) <= L,
+                "L is too small for K source symbols"
+            );
+            assert!(W * 32 >= L, "W is too small for L intermediate symbols");
+            assert!(T > 0, "T must not be 0");
+        }
+
+        let (l, l_prime, s, h, hp) = common::intermediate_symbols(K as u32);
+        self.k = K as u32;
+        self.l = l;
+        self.l_prime = l_prime;
+        self.coeff.iter_mut().for_each(|row| row.fill(0));
+        self.rank = 0;
+
+        let k = self.k;
+        let mut row = [0u32; W];
+        let mut b = [0u8; T];
+
+        // G_LDPC, I_S
+        for i in 0..s {
+            for j in 0..k {
+                if common::ldpc_rows(j, s).contains(&i) {
+                    toggle_bit(&mut row, j);
+                }
+            }
+            toggle_bit(&mut row, k + i);
+            self.add_equation(&mut row, &mut b);
+        }
+
+        // G_Half, I_H
+        for i in 0..h {
+            for (j, m) in common::gray_codes(hp).take((k + s) as usize).enumerate() {
+                if common::bit_set(m, i) {
+                    toggle_bit(&mut row, j as u32);
+                }
+            }
+            toggle_bit(&mut row, k + s + i);
+            self.add_equation(&mut row, &mut b);
+        }
+    }
+
+    /// Return the number of source symbols (k) inside the block
+    pub fn nb_source_symbols(&self) -> u32 {
+        self.k
+    }
+
+    /// Push an encoding symbol to the decoder
+    ///
+    /// Encoding symbols larger than `T` bytes are discarded.
+    ///
+    /// # Parameters
+    ///
+    /// * `encoding_symbol`: Data of the encoding symbol.
+    /// * `esi`: Encoding Symbol Identifier.
+    pub fn push_encoding_symbol(&mut self, encoding_symbol: &[u8], esi: u32) {
+        if encoding_symbol.len() > T {
+            #[cfg(feature = "log")]
+            log::warn!("Discard encoding symbol {} larger than {} bytes", esi, T);
+            return;
+        }
+
+        let mut row = [0u32; W];
+        common::for_each_lt_index(self.k, esi, self.l, self.l_prime, |i| {
+            toggle_bit(&mut row, i)
+        });
+
+        let mut b = [0u8; T];
+        b[..encoding_symbol.len()].copy_from_slice(encoding_symbol);
+        self.add_equation(&mut row, &mut b);
+    }
+
+    /// Return true when the block can be fully decoded
+    pub fn fully_specified(&self) -> bool {
+        self.rank == self.l as usize
+    }
+
+    /// Decode the source block into a buffer
+    ///
+    /// # Parameters
+    ///
+    /// * `source_block`: Buffer receiving the decoded source block, its length is the size of the source block in bytes.
+    ///
+    /// # Returns
+    ///
+    /// * `false` if the source block cannot be decoded or a source symbol is larger than `T` bytes
+    /// * `true` if the block is decoded into `source_block`
+    pub fn decode_into(&mut self, source_block: &mut [u8]) -> bool {
+        if !self.fully_specified() {
+            return false;
+        }
+
+        let partition = Partition::new(source_block.len(), self.k as usize);
+        if partition.long_size > T || partition.small_size > T {
+            return false;
+        }
+
+        self.reduce();
+
+        for i in 0..self.k {
+            let symbol = &mut source_block[partition.symbol_range(i as usize)];
+            symbol.fill(0);
+            common::for_each_lt_index(self.k, i, self.l, self.l_prime, |j| {
+                common::xor_u8(symbol, &self.intermediate[j as usize])
+            });
+        }
+        true
+    }
+
+    ///
+    /// On the fly Gaussian Elimination, see `SparseMatrix::add_equation`
+    ///
+    /// `row` and `b` are used as scratch buffers and are cleared.
+    ///
+    fn add_equation(&mut self, row: &mut [u32; W], b: &mut [u8; T]) {
+        while let Some(s) = first_bit(row) {
+            if self.coeff[s].iter().all(|word| *word == 0) {
+                core::mem::swap(&mut self.coeff[s], row);
+                core::mem::swap(&mut self.intermediate[s], b);
+                self.rank += 1;
+                break;
+            }
+
+            if count_ones(row) >= count_ones(&self.coeff[s]) {
+                for (word, coeff) in row.iter_mut().zip(&self.coeff[s]) {
+                    *word ^= coeff;
+                }
+                common::xor_u8(b, &self.intermediate[s]);
+            } else {
+                core::mem::swap(&mut self.coeff[s], row);
+                core::mem::swap(&mut self.intermediate[s], b);
+            }
+        }
+
+        row.fill(0);
+        b.fill(0);
+    }
+
+    /// Back substitution of the triangular matrix, see `SparseMatrix::reduce`
+    fn reduce(&mut self) {
+        for i in (0..self.l as usize).rev() {
+            let (inter_j, inter_i) = self.intermediate.split_at_mut(i);
+            for (coeff_j, inter_j) in self.coeff.iter().zip(inter_j.iter_mut()) {
+                if bit_set(coeff_j, i) {
+                    common::xor_u8(inter_j, &inter_i[0]);
+                }
+            }
+            self.coeff[i] = [0; W];
+            toggle_bit(&mut self.coeff[i], i as u32);
+        }
+    }
+}
+
+fn toggle_bit(row: &mut [u32], i: u32) {
+    row[i as usize / 32] ^= 1 << (i % 32);
+}
+
+fn bit_set(row: &[u32], i: usize) -> bool {
+    common::bit_set(row[i / 32], (i % 32) as u32)
+}
+
+fn first_bit(row: &[u32]) -> Option<usize> {
+    row.iter()
+        .position(|word| *word != 0)
+        .map(|w| w * 32 + row[w].trailing_zeros() as usize)
+}
+
+fn count_ones(row: &[u32]) -> u32 {
+    row.iter().map(|word| word.count_ones()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use rand::RngCore;
+
+    const K: usize = 10;
+    const T: usize = 16;
+    const L: usize = super::nb_intermediate_symbols(K);
+    const W: usize = super::nb_coefficient_words(L);
+
+    fn encode_decode(length: usize) {
+        crate::tests::init();
+
+        let mut data = vec![0u8; length];
+        rand::thread_rng().fill_bytes(&mut data);
+        let mut encoder = crate::SourceBlockEncoder::new(&data, K);
+        assert!(encoder.nb_source_symbols() == K as u32);
+
+        let mut decoder = super::StaticDecoder::<L, T, W>::new::<K>();
+        // Lose the first 3 source symbols
+        let mut esi = 3;
+        while !decoder.fully_specified() {
+            decoder.push_encoding_symbol(&encoder.fountain(esi), esi);
+            esi += 1;
+        }
+
+        let mut output = vec![0u8; length];
+        assert!(decoder.decode_into(&mut output));
+        assert!(output == data);
+    }
+
+    #[test]
+    fn test_static_decoder() {
+        encode_decode(K * T);
+    }
+
+    #[test]
+    fn test_static_decoder_partial_symbols() {
+        encode_decode(K * T - 7);
+    }
+
+    #[test]
+    fn test_static_decoder_precode() {
+        // Same precode constraints as the allocating decoder
+        let decoder = super::StaticDecoder::<L, T, W>::new::<K>();
+        let raptor = crate::raptor::Raptor::new(K as u32);
+        assert!(decoder.rank == raptor.rank() as usize);
+        assert!(decoder.coeff[0][0] == (1 << 0) | (1 << 5) | (1 << 6) | (1 << 7) | (1 << 10));
+    }
+
+    #[test]
+    fn test_static_decoder_init_in_place() {
+        crate::tests::init();
+
+        // 4.5 MB decoder, larger than the stack of the test thread
+        const K: usize = 2048;
+        const T: usize = 2048;
+        const L: usize = super::nb_intermediate_symbols(K);
+        const W: usize = super::nb_coefficient_words(L);
+
+        let mut memory = Box::<super::StaticDecoder<L, T, W>>::new_uninit();
+        let decoder = super::StaticDecoder::init::<K>(&mut memory);
+        assert!(decoder.nb_source_symbols() == K as u32);
+        assert!(decoder.rank == crate::raptor::Raptor::new(K as u32).rank() as usize);
+
+        decoder.push_encoding_symbol(&[1u8; T], 0);
+        decoder.reset::<K>();
+        assert!(decoder.rank == crate::raptor::Raptor::new(K as u32).rank() as usize);
+    }
+
+    #[test]
+    fn test_static_decoder_reject_large_symbol() {
+        let mut decoder = super::StaticDecoder::<L, T, W>::new::<K>();
+        decoder.push_encoding_symbol(&[0u8; T + 1], 0);
+        assert!(decoder.rank == super::StaticDecoder::<L, T, W>::new::<K>().rank);
+
+        let mut output = [0u8; K * T];
+        assert!(!decoder.decode_into(&mut output));
+    }
+}
@@ -0,0 +1,467 @@
+#[cfg(feature = "auth")]
+use crate::auth::SymbolAuthenticator;
+use crate::checksum::Checksum;
+use crate::partition::Partition;
+use crate::{SourceBlockDecoder, SourceBlockEncoder};
+use alloc::borrow::Cow;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// Return the byte range of every sub-symbol inside an encoding symbol, RFC 5053 section 5.3.1.2
+///
+/// The symbol is split into `nb_sub_blocks` sub-symbols of a multiple of `alignment` bytes.
+fn sub_symbol_ranges(
+    symbol_size: usize,
+    nb_sub_blocks: usize,
+    alignment: usize,
+) -> Vec<Range<usize>> {
+    assert!(
+        alignment > 0 && symbol_size.is_multiple_of(alignment),
+        "symbol size must be a multiple of the alignment"
+    );
+    assert!(
+        nb_sub_blocks > 0 && nb_sub_blocks <= symbol_size / alignment,
+        "number of sub-blocks out of range"
+    );
+
+    let partition = Partition::new(symbol_size / alignment, nb_sub_blocks);
+    (0..nb_sub_blocks)
+        .map(|n| {
+            let range = partition.symbol_range(n);
+            range.start * alignment..range.end * alignment
+        })
+        .collect()
+}
+
+/// Return the part of `symbol` covered by the sub-symbol `range`, the last symbol of a block can be truncated
+fn sub_symbol(symbol_length: usize, range: &Range<usize>) -> Range<usize> {
+    let end = range.end.min(symbol_length);
+    range.start.min(end)..end
+}
+
+///
+/// Encoder of a source block split into sub-blocks
+///
+/// Each source symbol of `symbol_size` bytes is split into N sub-symbols, and the sub-block `n`
+/// is made of the sub-symbols `n` of all the source symbols (RFC 5053 section 5.3.1.2).
+/// Every sub-block is encoded independently and an encoding symbol is the concatenation
+/// of the encoding symbols with the same ESI of all the sub-blocks.
+///
+/// The encoder holds the intermediate symbols of all the sub-blocks, about the size of the source block.
+/// Sub-blocks bound the working memory of the receivers, see [`SubBlockDecoder`].
+///
+pub struct SubBlockEncoder {
+    encoders: Vec<SourceBlockEncoder>,
+    sub_symbols: Vec<Range<usize>>,
+    checksum: Option<Checksum>,
+    #[cfg(feature = "auth")]
+    authenticator: Option<SymbolAuthenticator>,
+}
+
+impl SubBlockEncoder {
+    /// Create an encoder of a source block split into sub-blocks
+    ///
+    /// # Parameters
+    ///
+    /// * `source_block`: The source block, split into source symbols of `symbol_size` bytes.
+    /// * `symbol_size`: Size of an encoding symbol in bytes (T), a multiple of `alignment`.
+    /// * `nb_sub_blocks`: Number of sub-blocks (N), at most `symbol_size / alignment`.
+    /// * `alignment`: Alignment of the sub-symbols in bytes (Al).
+    ///
+    /// # Panics
+    ///
+    /// If `symbol_size`, `nb_sub_blocks` and `alignment` are inconsistent.
+    pub fn new(
+        source_block: &[u8],
+        symbol_size: usize,
+        nb_sub_blocks: usize,
+        alignment: usize,
+    ) -> Self {
+        let sub_symbols = sub_symbol_ranges(symbol_size, nb_sub_blocks, alignment);
+        let nb_source_symbols = source_block.len().div_ceil(symbol_size);
+
+        // A single sub-block is the source block itself, padded with zeros to K symbols of T bytes
+        let encoders = if nb_sub_blocks == 1 {
+            let mut source_block = Cow::Borrowed(source_block);
+            if source_block.len() != nb_source_symbols * symbol_size {
+                source_block
+                    .to_mut()
+                    .resize(nb_source_symbols * symbol_size, 0);
+            }
+            vec![SourceBlockEncoder::new(&source_block, nb_source_symbols)]
+        } else {
+            sub_symbols
+                .iter()
+                .map(|range| {
+                    let mut sub_block = Vec::with_capacity(nb_source_symbols * range.len());
+                    for symbol in source_block.chunks(symbol_size) {
+                        let start = sub_block.len();
+                        sub_block.extend_from_slice(&symbol[sub_symbol(symbol.len(), range)]);
+                        sub_block.resize(start + range.len(), 0);
+                    }
+                    SourceBlockEncoder::new(&sub_block, nb_source_symbols)
+                })
+                .collect()
+        };
+
+        SubBlockEncoder {
+            encoders,
+            sub_symbols,
+            checksum: None,
+            #[cfg(feature = "auth")]
+            authenticator: None,
+        }
+    }
+
+    /// Append a checksum to every encoding symbol generated by the encoder
+    ///
+    /// See [`SourceBlockEncoder::with_checksum`]
+    pub fn with_checksum(mut self, checksum: Checksum) -> Self {
+        self.checksum = Some(checksum);
+        self
+    }
+
+    /// Append an authentication tag to every encoding symbol generated by the encoder
+    ///
+    /// See [`SourceBlockEncoder::with_authenticator`]
+    #[cfg(feature = "auth")]
+    pub fn with_authenticator(mut self, authenticator: SymbolAuthenticator) -> Self {
+        self.authenticator = Some(authenticator);
+        self
+    }
+
+    /// Return the number of source symbols (k) inside the block
+    pub fn nb_source_symbols(&self) -> u32 {
+        self.encoders[0].nb_source_symbols()
+    }
+
+    /// Return the number of sub-blocks (N)
+    pub fn nb_sub_blocks(&self) -> usize {
+        self.encoders.len()
+    }
+
+    /// Generates the encoding symbol `esi`, made of one sub-symbol of every sub-block
+    ///
+    /// The authentication tag and the checksum are appended when configured.
+    pub fn fountain(&mut self, esi: u32) -> Vec<u8> {
+        self.fountain_group(esi, 1)
+    }
+
+    /// Generates a packet of `nb_symbols` consecutive encoding symbols, starting at `esi`
+    ///
+    /// See [`SourceBlockEncoder::fountain_group`]
+    pub fn fountain_group(&mut self, esi: u32, nb_symbols: u32) -> Vec<u8> {
+        let mut packet = match self.encoders.as_mut_slice() {
+            [encoder] => encoder.fountain_group(esi, nb_symbols),
+            encoders => {
+                let mut packet = Vec::new();
+                for i in 0..nb_symbols {
+                    let esi = esi.wrapping_add(i);
+                    for (encoder, range) in encoders.iter_mut().zip(&self.sub_symbols) {
+                        let start = packet.len();
+                        packet.extend_from_slice(&encoder.fountain(esi));
+                        packet.resize(start + range.len(), 0);
+                    }
+                }
+                packet
+            }
+        };
+
+        #[cfg(feature = "auth")]
+        if let Some(authenticator) = &self.authenticator {
+            authenticator.append(esi, &mut packet);
+        }
+        if let Some(checksum) = &self.checksum {
+            checksum.append(&mut packet);
+        }
+        packet
+    }
+}
+
+///
+/// Decoder of a source block split into sub-blocks
+///
+/// All the sub-blocks share the decoding matrix of the encoding symbols received, see [`SubBlockEncoder`].
+/// The matrix is eliminated as the encoding symbols are received, without their data,
+/// and the encoding symbols that increase its rank are stored.
+/// Once the block is fully specified, the sub-blocks are decoded one at a time from the stored symbols,
+/// so the working memory is bounded by the size of a sub-block instead of the size of the source block.
+/// The stored encoding symbols take about the size of the source block.
+///
+pub struct SubBlockDecoder {
+    /// Decoder of the source block when it is not split, decoding matrix without symbol data otherwise
+    decoder: SourceBlockDecoder,
+    /// Encoding symbols that increased the rank of the decoding matrix, when the block is split
+    received: Vec<(u32, Vec<u8>)>,
+    nb_source_symbols: usize,
+    sub_symbols: Vec<Range<usize>>,
+    symbol_size: usize,
+    checksum: Option<Checksum>,
+    #[cfg(feature = "auth")]
+    authenticator: Option<SymbolAuthenticator>,
+}
+
+impl SubBlockDecoder {
+    /// Create a decoder of a source block split into sub-blocks
+    ///
+    /// # Parameters
+    ///
+    /// * `nb_source_symbols`: Number of source symbols in the block.
+    /// * `symbol_size`: Size of an encoding symbol in bytes (T), a multiple of `alignment`.
+    /// * `nb_sub_blocks`: Number of sub-blocks (N), at most `symbol_size / alignment`.
+    /// * `alignment`: Alignment of the sub-symbols in bytes (Al).
+    ///
+    /// # Panics
+    ///
+    /// If `symbol_size`, `nb_sub_blocks` and `alignment` are inconsistent.
+    pub fn new(
+        nb_source_symbols: usize,
+        symbol_size: usize,
+        nb_sub_blocks: usize,
+        alignment: usize,
+    ) -> Self {
+        let sub_symbols = sub_symbol_ranges(symbol_size, nb_sub_blocks, alignment);
+
+        SubBlockDecoder {
+            decoder: SourceBlockDecoder::new(nb_source_symbols),
+            received: Vec::new(),
+            nb_source_symbols,
+            sub_symbols,
+            symbol_size,
+            checksum: None,
+            #[cfg(feature = "auth")]
+            authenticator: None,
+        }
+    }
+
+    /// Verify the checksum appended to every encoding symbol
+    ///
+    /// See [`SourceBlockDecoder::with_checksum`]
+    pub fn with_checksum(mut self, checksum: Checksum) -> Self {
+        self.checksum = Some(checksum);
+        self
+    }
+
+    /// Verify the authentication tag appended to every encoding symbol
+    ///
+    /// See [`SourceBlockDecoder::with_authenticator`]
+    #[cfg(feature = "auth")]
+    pub fn with_authenticator(mut self, authenticator: SymbolAuthenticator) -> Self {
+        self.authenticator = Some(authenticator);
+        self
+    }
+
+    /// Return the number of sub-blocks (N)
+    pub fn nb_sub_blocks(&self) -> usize {
+        self.sub_symbols.len()
+    }
+
+    /// Push an encoding symbol to the decoder
+    ///
+    /// # Arguments
+    ///
+    /// * `encoding_symbol` - The encoding symbol, made of one sub-symbol of every sub-block
+    /// * `esi` - Encoding symbol identifier (ESI)
+    pub fn push_encoding_symbol(&mut self, encoding_symbol: &[u8], esi: u32) {
+        self.push_encoding_symbol_group(encoding_symbol, esi, 1);
+    }
+
+    /// Push a packet of `nb_symbols` consecutive encoding symbols to the decoder
+    ///
+    /// See [`SourceBlockDecoder::push_encoding_symbol_group`]
+    pub fn push_encoding_symbol_group(&mut self, packet: &[u8], esi: u32, nb_symbols: u32) {
+        let packet = match self.verify_integrity(packet, esi) {
+            Some(length) => &packet[..length],
+            None => {
+                #[cfg(feature = "log")]
+                log::warn!(
+                    "Discard corrupted or unauthenticated encoding symbol {}",
+                    esi
+                );
+                return;
+            }
+        };
+
+        if self.sub_symbols.len() == 1 {
+            self.decoder
+                .push_encoding_symbol_group(packet, esi, nb_symbols);
+            return;
+        }
+
+        if packet.len() != nb_symbols as usize * self.symbol_size {
+            #[cfg(feature = "log")]
+            log::warn!(
+                "Discard packet of {} bytes, expected {} encoding symbols of {} bytes",
+                packet.len(),
+                nb_symbols,
+                self.symbol_size
+            );
+            return;
+        }
+
+        for (i, symbol) in packet.chunks_exact(self.symbol_size).enumerate() {
+            let esi = esi.wrapping_add(i as u32);
+            let nb_useful = self.decoder.statistics().nb_useful();
+            self.decoder.push_encoding_symbol(&[], esi);
+            if self.decoder.statistics().nb_useful() > nb_useful {
+                self.received.push((esi, symbol.to_vec()));
+            }
+        }
+    }
+
+    /// Return true when all the sub-blocks can be fully decoded
+    pub fn fully_specified(&self) -> bool {
+        self.decoder.fully_specified()
+    }
+
+    /// Decode the source block
+    ///
+    /// # Parameters
+    ///
+    /// * `source_block_length`: The size of the source block in bytes.
+    ///
+    /// # Returns
+    ///
+    /// * `None` if the source block cannot be decoded
+    /// * `Some(Vec<u8>)` if the block is decoded
+    pub fn decode(&mut self, source_block_length: usize) -> Option<Vec<u8>> {
+        let mut source_block = vec![0u8; source_block_length];
+        self.decode_into(&mut source_block).then_some(source_block)
+    }
+
+    /// Decode the source block into a buffer
+    ///
+    /// The sub-blocks are decoded one at a time, their sub-symbols are written directly at their offset
+    /// inside `source_block`. The length of the buffer is the size of the source block in bytes.
+    ///
+    /// # Returns
+    ///
+    /// * `false` if the source block cannot be decoded
+    /// * `true` if the block is decoded into `source_block`
+    pub fn decode_into(&mut self, source_block: &mut [u8]) -> bool {
+        if !self.fully_specified() {
+            return false;
+        }
+
+        if self.sub_symbols.len() == 1 {
+            let padded_length = self.nb_source_symbols * self.symbol_size;
+            if source_block.len() == padded_length {
+                return self.decoder.decode_into(source_block);
+            }
+
+            // The last source symbol is padded with zeros to T bytes
+            let mut padded = vec![0u8; padded_length];
+            if !self.decoder.decode_into(&mut padded) {
+                return false;
+            }
+            let length = source_block.len().min(padded_length);
+            source_block[..length].copy_from_slice(&padded[..length]);
+            return true;
+        }
+
+        for range in &self.sub_symbols {
+            // The decoder of a sub-block is dropped before the next one is created
+            let mut decoder = SourceBlockDecoder::new(self.nb_source_symbols);
+            for (esi, symbol) in &self.received {
+                decoder.push_encoding_symbol(&symbol[range.clone()], *esi);
+            }
+
+            let mut sub_symbols: Vec<&mut [u8]> = source_block
+                .chunks_mut(self.symbol_size)
+                .map(|symbol| {
+                    let range = sub_symbol(symbol.len(), range);
+                    &mut symbol[range]
+                })
+                .collect();
+            if !decoder.decode_symbols_into(&mut sub_symbols) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Verify the checksum and the authentication tag of a packet
+    ///
+    /// Return the length of the packet data, or `None` if the packet must be discarded
+    #[cfg_attr(not(feature = "auth"), allow(unused_variables))]
+    fn verify_integrity(&self, packet: &[u8], esi: u32) -> Option<usize> {
+        let mut length = packet.len();
+        if let Some(checksum) = &self.checksum {
+            length = checksum.verify(packet)?;
+        }
+
+        #[cfg(feature = "auth")]
+        if let Some(authenticator) = &self.authenticator {
+            length = authenticator.verify(esi, &packet[..length])?;
+        }
+
+        Some(length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use rand::RngCore;
+
+    #[test]
+    fn test_sub_symbol_ranges() {
+        // T/Al = 10 units split into 3 sub-symbols of 4, 3 and 3 units
+        let ranges = super::sub_symbol_ranges(40, 3, 4);
+        assert!(ranges == vec![0..16, 16..28, 28..40]);
+    }
+
+    #[test]
+    fn test_sub_block_encode_decode() {
+        crate::tests::init();
+
+        for (length, nb_sub_blocks) in [(30 * 64, 4), (30 * 64 - 37, 4), (30 * 64 - 5, 1)] {
+            let mut data = vec![0u8; length];
+            rand::thread_rng().fill_bytes(&mut data);
+
+            let mut encoder = super::SubBlockEncoder::new(&data, 64, nb_sub_blocks, 4)
+                .with_checksum(crate::Checksum::Crc32c);
+            assert!(encoder.nb_source_symbols() == 30);
+            assert!(encoder.nb_sub_blocks() == nb_sub_blocks);
+
+            let mut decoder = super::SubBlockDecoder::new(30, 64, nb_sub_blocks, 4)
+                .with_checksum(crate::Checksum::Crc32c);
+            // Lose the first 4 source symbols, receive every symbol twice
+            let mut esi = 4;
+            while !decoder.fully_specified() {
+                decoder.push_encoding_symbol(&encoder.fountain(esi), esi);
+                decoder.push_encoding_symbol(&encoder.fountain(esi), esi);
+                esi += 1;
+            }
+
+            // Only the symbols increasing the rank are stored for the sub-blocks
+            if nb_sub_blocks > 1 {
+                assert!(decoder.received.len() == 30);
+            }
+            assert!(decoder.decode(length).unwrap() == data);
+            assert!(decoder.decode(length).unwrap() == data);
+        }
+    }
+
+    #[test]
+    fn test_sub_block_groups() {
+        crate::tests::init();
+
+        let mut data = vec![0u8; 20 * 48];
+        rand::thread_rng().fill_bytes(&mut data);
+        let mut encoder = super::SubBlockEncoder::new(&data, 48, 3, 8);
+        let mut decoder = super::SubBlockDecoder::new(20, 48, 3, 8);
+
+        let packet = encoder.fountain_group(0, 4);
+        assert!(packet.len() == 4 * 48);
+        assert!(packet[48..96] == encoder.fountain(1));
+
+        let mut esi = 0;
+        while !decoder.fully_specified() {
+            decoder.push_encoding_symbol_group(&encoder.fountain_group(esi, 4), esi, 4);
+            esi += 4;
+        }
+        assert!(decoder.decode(data.len()).unwrap() == data);
+    }
+}
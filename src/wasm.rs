@@ -0,0 +1,138 @@
+//!
+//! WebAssembly bindings
+//!
+//! Source block encoder and decoder for JavaScript, encoding symbols and source blocks are `Uint8Array`.
+//!
+
+use crate::{SourceBlockDecoder, SourceBlockEncoder, MAX_SOURCE_SYMBOLS_PER_BLOCK};
+use alloc::vec::Vec;
+use wasm_bindgen::prelude::*;
+
+/// Check a number of source symbols, blocks of less than 4 symbols are padded by the encoder and the decoder
+fn check_nb_source_symbols(nb_source_symbols: usize) -> Result<(), JsError> {
+    if nb_source_symbols == 0 || nb_source_symbols > MAX_SOURCE_SYMBOLS_PER_BLOCK as usize {
+        return Err(JsError::new("number of source symbols out of range"));
+    }
+    Ok(())
+}
+
+/// On the fly source block encoder
+#[wasm_bindgen(js_name = SourceBlockEncoder)]
+pub struct WasmSourceBlockEncoder {
+    encoder: SourceBlockEncoder,
+}
+
+#[wasm_bindgen(js_class = SourceBlockEncoder)]
+impl WasmSourceBlockEncoder {
+    /// Create an encoder for the source block `source_block`
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        source_block: &[u8],
+        max_source_symbols: usize,
+    ) -> Result<WasmSourceBlockEncoder, JsError> {
+        check_nb_source_symbols(max_source_symbols)?;
+        if source_block.is_empty() {
+            return Err(JsError::new("source block is empty"));
+        }
+
+        Ok(WasmSourceBlockEncoder {
+            encoder: SourceBlockEncoder::new(source_block, max_source_symbols),
+        })
+    }
+
+    /// Number of source symbols (k) inside the block
+    #[wasm_bindgen(getter = nbSourceSymbols)]
+    pub fn nb_source_symbols(&self) -> u32 {
+        self.encoder.nb_source_symbols()
+    }
+
+    /// Generate the encoding symbol `esi`
+    pub fn fountain(&mut self, esi: u32) -> Vec<u8> {
+        self.encoder.fountain(esi)
+    }
+}
+
+/// On the fly source block decoder
+#[wasm_bindgen(js_name = SourceBlockDecoder)]
+pub struct WasmSourceBlockDecoder {
+    decoder: SourceBlockDecoder,
+}
+
+#[wasm_bindgen(js_class = SourceBlockDecoder)]
+impl WasmSourceBlockDecoder {
+    /// Create a decoder for a source block of `nb_source_symbols` source symbols
+    #[wasm_bindgen(constructor)]
+    pub fn new(nb_source_symbols: usize) -> Result<WasmSourceBlockDecoder, JsError> {
+        check_nb_source_symbols(nb_source_symbols)?;
+        Ok(WasmSourceBlockDecoder {
+            decoder: SourceBlockDecoder::new(nb_source_symbols),
+        })
+    }
+
+    /// Push an encoding symbol to the decoder
+    #[wasm_bindgen(js_name = pushEncodingSymbol)]
+    pub fn push_encoding_symbol(&mut self, encoding_symbol: &[u8], esi: u32) {
+        self.decoder.push_encoding_symbol(encoding_symbol, esi)
+    }
+
+    /// Return true when the block can be fully decoded
+    #[wasm_bindgen(js_name = fullySpecified)]
+    pub fn fully_specified(&self) -> bool {
+        self.decoder.fully_specified()
+    }
+
+    /// Decode the source block, return `undefined` if the block cannot be decoded yet
+    pub fn decode(&mut self, source_block_length: usize) -> Option<Vec<u8>> {
+        self.decoder.decode(source_block_length)
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn test_wasm_encode_decode() {
+        let source_block: Vec<u8> = (0..30 * 32 - 3).map(|i| (i * 7) as u8).collect();
+        let mut encoder = super::WasmSourceBlockEncoder::new(&source_block, 30).unwrap();
+        assert!(encoder.nb_source_symbols() == 30);
+
+        let mut decoder = super::WasmSourceBlockDecoder::new(30).unwrap();
+        assert!(decoder.decode(source_block.len()).is_none());
+
+        // Lose the first 5 source symbols
+        let mut esi = 5;
+        while !decoder.fully_specified() {
+            decoder.push_encoding_symbol(&encoder.fountain(esi), esi);
+            esi += 1;
+        }
+        assert!(decoder.decode(source_block.len()).unwrap() == source_block);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_wasm_invalid_arguments() {
+        assert!(super::WasmSourceBlockDecoder::new(0).is_err());
+        assert!(super::WasmSourceBlockEncoder::new(&[], 10).is_err());
+        assert!(super::WasmSourceBlockEncoder::new(&[1, 2, 3], 10000).is_err());
+
+        // Blocks of 2 and 3 source symbols are padded instead of trapping
+        let source_block: Vec<u8> = (0..100).collect();
+        for (source_block, max_source_symbols, k) in [
+            (&[1u8][..], 10, 2),
+            (&source_block[..], 2, 2),
+            (&source_block[..], 3, 3),
+        ] {
+            let mut encoder =
+                super::WasmSourceBlockEncoder::new(source_block, max_source_symbols).unwrap();
+            assert!(encoder.nb_source_symbols() == k);
+            let mut decoder = super::WasmSourceBlockDecoder::new(k as usize).unwrap();
+            let mut esi = k;
+            while !decoder.fully_specified() {
+                decoder.push_encoding_symbol(&encoder.fountain(esi), esi);
+                esi += 1;
+            }
+            assert!(decoder.decode(source_block.len()).unwrap() == source_block);
+        }
+    }
+}
@@ -0,0 +1,222 @@
+mod tests {
+
+    use rand::RngCore;
+    use raptor_code::alc::{AlcPacket, LctHeader, FDT_TOI};
+    #[cfg(feature = "fdt")]
+    use raptor_code::fdt::FdtInstance;
+    use std::collections::HashMap;
+
+    pub fn init() {
+        std::env::set_var("RUST_LOG", "debug");
+        env_logger::builder().is_test(true).try_init().ok();
+    }
+
+    const TSI: u64 = 42;
+
+    /// Generate the ALC packets of an object, every packet carries the FEC Object Transmission Information
+    fn send_object(
+        toi: u64,
+        object: &[u8],
+        fdt_instance_id: Option<u32>,
+        nb_repair: u32,
+    ) -> Vec<Vec<u8>> {
+        let encoder = raptor_code::ObjectEncoder::new(object, 128, 64).unwrap();
+        object_packets(toi, &encoder, fdt_instance_id, nb_repair)
+    }
+
+    /// Generate the ALC packets of the source blocks of `encoder`
+    fn object_packets(
+        toi: u64,
+        encoder: &raptor_code::ObjectEncoder<&[u8]>,
+        fdt_instance_id: Option<u32>,
+        nb_repair: u32,
+    ) -> Vec<Vec<u8>> {
+        let oti = *encoder.transmission_information();
+
+        let mut packets = Vec::new();
+        for sbn in 0..encoder.nb_source_blocks() {
+            let mut block_encoder = encoder.source_block_encoder(sbn).unwrap();
+            for esi in 0..block_encoder.nb_source_symbols() + nb_repair {
+                let mut header = LctHeader::new(TSI, toi);
+                header.fti = Some(oti);
+                header.fdt_instance_id = fdt_instance_id;
+                header.close_object = sbn + 1 == encoder.nb_source_blocks()
+                    && esi + 1 == block_encoder.nb_source_symbols() + nb_repair;
+
+                let encoding_symbol = block_encoder.fountain(esi);
+                let packet = AlcPacket::new(header, sbn as u16, esi as u16, &encoding_symbol);
+                packets.push(packet.to_bytes().unwrap());
+            }
+        }
+        packets
+    }
+
+    /// Receiver of a FLUTE session, objects are decoded as their packets arrive
+    #[derive(Default)]
+    struct Receiver {
+        objects: HashMap<u64, raptor_code::ObjectDecoder<Vec<u8>>>,
+        fdt_instance_id: Option<u32>,
+        closed_objects: Vec<u64>,
+        closed: bool,
+    }
+
+    impl Receiver {
+        fn receive(&mut self, data: &[u8]) {
+            let packet = AlcPacket::parse(data).unwrap();
+            if packet.header.tsi != TSI {
+                return;
+            }
+
+            if packet.header.toi == FDT_TOI {
+                self.fdt_instance_id = packet.header.fdt_instance_id;
+            }
+            if packet.header.close_object {
+                self.closed_objects.push(packet.header.toi);
+            }
+            self.closed |= packet.header.close_session;
+
+            let Some(oti) = packet.header.fti else {
+                return;
+            };
+            let decoder = self.objects.entry(packet.header.toi).or_insert_with(|| {
+                raptor_code::ObjectDecoder::new(oti, vec![0u8; oti.transfer_length as usize])
+                    .unwrap()
+            });
+            decoder.push_encoding_symbol(packet.sbn as u32, packet.esi as u32, packet.payload);
+        }
+    }
+
+    #[test]
+    pub fn test_alc_session() {
+        init();
+
+        let fdt = br#"<?xml version="1.0" encoding="UTF-8"?>
+<FDT-Instance Expires="3600"><File TOI="1" Content-Location="file.bin"/></FDT-Instance>"#;
+        let mut file = vec![0u8; 20 * 1024 + 13];
+        rand::thread_rng().fill_bytes(&mut file);
+
+        let mut packets = send_object(FDT_TOI, fdt, Some(1), 4);
+        packets.extend(send_object(1, &file, None, 30));
+
+        let mut close_session = LctHeader::new(TSI, 1);
+        close_session.close_session = true;
+        packets.push(AlcPacket::new(close_session, 0, 0, &[]).to_bytes().unwrap());
+
+        // Lose one packet out of 5, except the packets closing an object or the session
+        let mut receiver = Receiver::default();
+        for (i, packet) in packets.iter().enumerate() {
+            let header = AlcPacket::parse(packet).unwrap().header;
+            if i % 5 != 1 || header.close_object || header.close_session {
+                receiver.receive(packet);
+            }
+        }
+
+        assert!(receiver.closed);
+        assert!(receiver.fdt_instance_id == Some(1));
+        assert!(receiver.closed_objects == vec![FDT_TOI, 1]);
+
+        let fdt_decoder = receiver.objects.remove(&FDT_TOI).unwrap();
+        assert!(fdt_decoder.finish().unwrap() == fdt);
+        let file_decoder = receiver.objects.remove(&1).unwrap();
+        assert!(file_decoder.finish().unwrap() == file);
+    }
+
+    #[test]
+    pub fn test_flute_packet_layout() {
+        init();
+
+        // 20 bytes in 3 symbols of 8 bytes (RFC 5053 section 5.3.1.2): Z = 2 source blocks of 2 and 1 symbols,
+        // the last symbol of the object is padded with zeros to T bytes
+        let object: Vec<u8> = (0..20).collect();
+        let encoder = raptor_code::ObjectEncoder::new(&object[..], 8, 2).unwrap();
+        let packets = object_packets(1, &encoder, None, 2);
+        assert!(packets.len() == 2 + 2 + 1 + 2);
+
+        #[rustfmt::skip]
+        let lct_header = [
+            // V = 1, C = 0, PSI = 0 | S = 1, O = 1, H = 0 | HDR_LEN = 8 words | Codepoint = 1 (Raptor)
+            0x10, 0xA0, 8, 1,
+            // CCI
+            0, 0, 0, 0,
+            // TSI
+            0, 0, 0, 42,
+            // TOI
+            0, 0, 0, 1,
+            // EXT_FTI (RFC 5775 section 5.2): HET = 64, HEL = 4
+            64, 4,
+            // Transfer Length (40 bits), Reserved, Encoding Symbol Length (RFC 5053 section 3.2.2)
+            0, 0, 0, 0, 20, 0, 0, 8,
+            // Z, N, Al (RFC 5053 section 3.2.3), padding
+            0, 2, 1, 1, 0, 0,
+        ];
+        let expected = |sbn: u8, esi: u8, symbol: &[u8]| {
+            let mut packet = lct_header.to_vec();
+            // FEC Payload ID (RFC 5053 section 3.1.2): SBN, ESI
+            packet.extend_from_slice(&[0, sbn, 0, esi]);
+            packet.extend_from_slice(symbol);
+            packet
+        };
+
+        assert!(packets[0] == expected(0, 0, &[0, 1, 2, 3, 4, 5, 6, 7]));
+        assert!(packets[1] == expected(0, 1, &[8, 9, 10, 11, 12, 13, 14, 15]));
+        assert!(packets[4] == expected(1, 0, &[16, 17, 18, 19, 0, 0, 0, 0]));
+
+        // Repair symbols are T bytes long, including in the last source block
+        for packet in &packets {
+            assert!(packet.len() == lct_header.len() + 4 + 8);
+        }
+        // The last packet of the object sets the Close Object flag
+        assert!(packets[6][1] == 0xA1);
+    }
+
+    #[cfg(feature = "fdt")]
+    #[test]
+    pub fn test_flute_fdt() {
+        init();
+
+        let mut file = vec![0u8; 8 * 1024 + 5];
+        rand::thread_rng().fill_bytes(&mut file);
+        let encoder = raptor_code::ObjectEncoder::new(&file, 256, 16)
+            .unwrap()
+            .with_sub_blocks(2, 4)
+            .unwrap();
+
+        let mut fdt = FdtInstance::new(3600);
+        fdt.files.push(encoder.fdt_file(7, "file:///data/file.bin"));
+        let fdt_xml = fdt.to_xml();
+
+        // The FDT instance is delivered first, with its transmission information in EXT_FTI
+        let mut receiver = Receiver::default();
+        for packet in send_object(FDT_TOI, fdt_xml.as_bytes(), Some(1), 6) {
+            receiver.receive(&packet);
+        }
+        let fdt_decoder = receiver.objects.remove(&FDT_TOI).unwrap();
+        let received_fdt = fdt_decoder.finish().unwrap();
+        let received_fdt =
+            FdtInstance::from_xml(std::str::from_utf8(&received_fdt).unwrap()).unwrap();
+        assert!(received_fdt == fdt);
+
+        // The file decoder is configured from the FDT only
+        let fdt_file = received_fdt.file(7).unwrap();
+        let mut decoder = fdt_file
+            .object_decoder(vec![0u8; fdt_file.content_length.unwrap() as usize])
+            .unwrap();
+        for sbn in 0..encoder.nb_source_blocks() {
+            let mut block_encoder = encoder.sub_block_encoder(sbn).unwrap();
+            for esi in 1..block_encoder.nb_source_symbols() + 4 {
+                let encoding_symbol = block_encoder.fountain(esi);
+                let packet = AlcPacket::new(
+                    LctHeader::new(TSI, 7),
+                    sbn as u16,
+                    esi as u16,
+                    &encoding_symbol,
+                )
+                .to_bytes()
+                .unwrap();
+                let packet = AlcPacket::parse(&packet).unwrap();
+                decoder.push_encoding_symbol(packet.sbn as u32, packet.esi as u32, packet.payload);
+            }
+        }
+        assert!(decoder.finish().unwrap() == file);
+    }
+}
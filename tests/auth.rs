@@ -0,0 +1,128 @@
+#![cfg(feature = "auth")]
+
+mod tests {
+
+    use rand::{Rng, RngCore};
+    use raptor_code::SymbolAuthenticator;
+
+    pub fn init() {
+        std::env::set_var("RUST_LOG", "debug");
+        env_logger::builder().is_test(true).try_init().ok();
+    }
+
+    fn create_source_block_data(length: usize) -> Vec<u8> {
+        let mut output = vec![0u8; length];
+        rand::thread_rng().fill_bytes(output.as_mut());
+        output
+    }
+
+    #[test]
+    pub fn test_forged_symbols_rejected() {
+        init();
+        let key = b"multicast group key";
+        let source_block_data = create_source_block_data(32 * 256);
+
+        let mut encoder = raptor_code::SourceBlockEncoder::new(&source_block_data, 32)
+            .with_authenticator(SymbolAuthenticator::new(key));
+        let mut attacker =
+            raptor_code::SourceBlockEncoder::new(&create_source_block_data(32 * 256), 32)
+                .with_authenticator(SymbolAuthenticator::new(b"guessed key"));
+        let mut decoder = raptor_code::SourceBlockDecoder::new(32)
+            .with_authenticator(SymbolAuthenticator::new(key));
+
+        let mut rng = rand::thread_rng();
+        let mut nb_forged = 0;
+        let mut esi = 0;
+        while !decoder.fully_specified() {
+            // The attacker injects forged symbols with the ESI expected by the receiver
+            let forged = match rng.gen_range(0..3) {
+                0 => attacker.fountain(esi),
+                1 => create_source_block_data(256 + SymbolAuthenticator::TAG_SIZE),
+                _ => {
+                    let mut replay = encoder.fountain(esi + 1);
+                    replay[0] ^= 0xFF;
+                    replay
+                }
+            };
+            decoder.push_encoding_symbol(&forged, esi);
+            nb_forged += 1;
+
+            decoder.push_encoding_symbol(&encoder.fountain(esi), esi);
+            esi += 1;
+        }
+
+        let stats = decoder.statistics();
+        assert!(stats.nb_unauthenticated == nb_forged);
+        assert!(stats.nb_duplicate == 0);
+
+        let output = decoder.decode(source_block_data.len()).unwrap();
+        assert!(output == source_block_data);
+    }
+
+    #[test]
+    pub fn test_symbols_bound_to_source_block() {
+        init();
+        let authenticator = SymbolAuthenticator::new(b"key");
+        let object = create_source_block_data(4 * 1024);
+        let encoder = raptor_code::ObjectEncoder::new(&object, 256, 8)
+            .unwrap()
+            .with_authenticator(authenticator.clone());
+        let oti = *encoder.transmission_information();
+        assert!(oti.nb_source_blocks == 2);
+
+        let mut decoder = raptor_code::ObjectDecoder::new(oti, vec![0u8; object.len()])
+            .unwrap()
+            .with_authenticator(authenticator);
+
+        // Replay the symbols of block 0 as symbols of block 1
+        let mut block_encoder = encoder.source_block_encoder(0).unwrap();
+        for esi in 0..20 {
+            decoder.push_encoding_symbol(1, esi, &block_encoder.fountain(esi));
+        }
+        assert!(!decoder.is_block_decoded(0));
+        assert!(!decoder.is_block_decoded(1));
+
+        for sbn in 0..2 {
+            let mut block_encoder = encoder.source_block_encoder(sbn).unwrap();
+            for esi in 0..12 {
+                decoder.push_encoding_symbol(sbn, esi, &block_encoder.fountain(esi));
+            }
+        }
+
+        assert!(decoder.fully_decoded());
+        assert!(decoder.finish().unwrap() == object);
+    }
+
+    #[test]
+    pub fn test_symbols_bound_to_transfer() {
+        init();
+        let authenticator = SymbolAuthenticator::new(b"session key");
+        let first = create_source_block_data(4 * 1024);
+        let second = create_source_block_data(4 * 1024);
+        let first_encoder = raptor_code::ObjectEncoder::new(&first, 256, 16)
+            .unwrap()
+            .with_authenticator(authenticator.for_transfer(1));
+        let second_encoder = raptor_code::ObjectEncoder::new(&second, 256, 16)
+            .unwrap()
+            .with_authenticator(authenticator.for_transfer(2));
+        let oti = *second_encoder.transmission_information();
+
+        let mut decoder = raptor_code::ObjectDecoder::new(oti, vec![0u8; second.len()])
+            .unwrap()
+            .with_authenticator(authenticator.for_transfer(2));
+
+        // Replay the symbols of the first object into the decoder of the second one
+        let mut block_encoder = first_encoder.source_block_encoder(0).unwrap();
+        for esi in 0..20 {
+            decoder.push_encoding_symbol(0, esi, &block_encoder.fountain(esi));
+        }
+        assert!(!decoder.is_block_decoded(0));
+
+        let mut block_encoder = second_encoder.source_block_encoder(0).unwrap();
+        for esi in 0..16 {
+            decoder.push_encoding_symbol(0, esi, &block_encoder.fountain(esi));
+        }
+        assert!(decoder.fully_decoded());
+        assert!(decoder.finish().unwrap() == second);
+    }
+}
@@ -0,0 +1,176 @@
+mod tests {
+
+    #[cfg(feature = "manifest")]
+    use rand::Rng;
+    use rand::RngCore;
+    use raptor_code::ccsds::{SpacePacket, SpacePacketDecoder, SpacePacketEncoder};
+    #[cfg(feature = "manifest")]
+    use raptor_code::{Checksum, ObjectManifest};
+    use raptor_code::{ObjectDecoder, ObjectEncoder};
+
+    pub fn init() {
+        std::env::set_var("RUST_LOG", "debug");
+        env_logger::builder().is_test(true).try_init().ok();
+    }
+
+    const FILE_APID: u16 = 0x42;
+    #[cfg(feature = "manifest")]
+    const HOUSEKEEPING_APID: u16 = 0x10;
+
+    #[cfg(feature = "manifest")]
+    #[test]
+    pub fn test_file_downlink() {
+        init();
+
+        let mut file = vec![0u8; 200 * 1024 + 77];
+        rand::thread_rng().fill_bytes(&mut file);
+
+        // On board: the file is encoded with a CRC32C per encoding symbol,
+        // the manifest is uplinked to the ground beforehand
+        let encoder = ObjectEncoder::new(&file, 1024, 64)
+            .unwrap()
+            .with_checksum(Checksum::Crc32c);
+        let manifest = encoder.manifest().to_bytes();
+        let mut file_packets = SpacePacketEncoder::new(FILE_APID).unwrap();
+        let mut housekeeping = SpacePacketEncoder::new(HOUSEKEEPING_APID).unwrap();
+        let packets = file_packets.object_packets(&encoder, 32).unwrap();
+
+        // Ground: 20% of the packets are lost, except the first and the last one,
+        // housekeeping packets are interleaved and a packet is corrupted by the link
+        let manifest = ObjectManifest::from_bytes(&manifest).unwrap();
+        let decoder = ObjectDecoder::from_manifest(&manifest, vec![0u8; file.len()])
+            .unwrap()
+            .with_checksum(Checksum::Crc32c);
+        let mut ground = SpacePacketDecoder::new(FILE_APID, decoder);
+
+        let mut rng = rand::thread_rng();
+        let mut nb_lost = 0;
+        for (i, packet) in packets.iter().enumerate() {
+            if i % 10 == 0 {
+                let telemetry = housekeeping.encode(0, i as u16, 1, &[0x55; 32]).unwrap();
+                ground.push(&telemetry).unwrap();
+            }
+            if i != 0 && i + 1 != packets.len() && rng.gen_bool(0.2) {
+                nb_lost += 1;
+                continue;
+            }
+
+            let mut packet = packet.clone();
+            if i == 3 {
+                let last = packet.len() - 10;
+                packet[last] ^= 0xFF;
+            }
+            ground.push(&packet).unwrap();
+        }
+
+        assert!(ground.fully_decoded());
+        assert!(ground.nb_lost_packets() == nb_lost);
+        assert!(ground.nb_received_packets() == packets.len() as u32 - nb_lost);
+        assert!(ground.finish().unwrap() == file);
+    }
+
+    #[test]
+    pub fn test_sub_blocks_downlink() {
+        init();
+
+        let mut file = vec![0u8; 30 * 1024 + 3];
+        rand::thread_rng().fill_bytes(&mut file);
+        let encoder = ObjectEncoder::new(&file, 512, 32)
+            .unwrap()
+            .with_sub_blocks(4, 8)
+            .unwrap();
+        let oti = *encoder.transmission_information();
+        let packets = SpacePacketEncoder::new(7)
+            .unwrap()
+            .object_packets(&encoder, 16)
+            .unwrap();
+
+        // Every 4th packet is lost and the packets are received in reverse order
+        let decoder = ObjectDecoder::new(oti, vec![0u8; file.len()]).unwrap();
+        let mut ground = SpacePacketDecoder::new(7, decoder);
+        for (i, packet) in packets.iter().enumerate().rev() {
+            assert!(SpacePacket::parse(packet).unwrap().sequence_count == i as u16);
+            if i % 4 != 1 {
+                ground.push(packet).unwrap();
+            }
+        }
+        assert!(ground.finish().unwrap() == file);
+    }
+
+    #[test]
+    pub fn test_small_file_downlink() {
+        init();
+
+        // Files of less than 4 symbols
+        for (length, k) in [(1, 1), (200, 2), (300, 3)] {
+            let mut file = vec![0u8; length];
+            rand::thread_rng().fill_bytes(&mut file);
+            let encoder = ObjectEncoder::new(&file, 128, 64).unwrap();
+            let oti = *encoder.transmission_information();
+            let packets = SpacePacketEncoder::new(FILE_APID)
+                .unwrap()
+                .object_packets(&encoder, 3)
+                .unwrap();
+            assert!(packets.len() == k + 3);
+
+            let decoder = ObjectDecoder::new(oti, vec![0u8; length]).unwrap();
+            let mut ground = SpacePacketDecoder::new(FILE_APID, decoder);
+            for packet in packets.iter().skip(1) {
+                ground.push(packet).unwrap();
+            }
+            assert!(ground.finish().unwrap() == file);
+        }
+    }
+
+    #[test]
+    pub fn test_mismatched_source_block_length() {
+        init();
+
+        let file = vec![1u8; 4096];
+        let encoder = ObjectEncoder::new(&file, 256, 16).unwrap();
+        let oti = *encoder.transmission_information();
+        let mut downlink = SpacePacketEncoder::new(1).unwrap();
+
+        let decoder = ObjectDecoder::new(oti, vec![0u8; file.len()]).unwrap();
+        let mut ground = SpacePacketDecoder::new(1, decoder);
+        let packet = downlink.encode(0, 0, 15, &[0u8; 256]).unwrap();
+        assert!(ground.push(&packet).is_err());
+        let packet = downlink.encode(1, 0, 16, &[0u8; 256]).unwrap();
+        assert!(ground.push(&packet).is_err());
+        assert!(ground.nb_received_packets() == 0);
+    }
+
+    #[test]
+    pub fn test_foreign_packets() {
+        init();
+
+        let file = vec![1u8; 4096];
+        let encoder = ObjectEncoder::new(&file, 256, 16).unwrap();
+        let oti = *encoder.transmission_information();
+        let decoder = ObjectDecoder::new(oti, vec![0u8; file.len()]).unwrap();
+        let mut ground = SpacePacketDecoder::new(FILE_APID, decoder);
+
+        // Telemetry packet of another APID without secondary header
+        let foreign = [0x00, 0x10, 0xC0, 0x00, 0x00, 0x01, 0xAA, 0xBB];
+        assert!(SpacePacket::parse(&foreign).is_err());
+        assert!(ground.push(&foreign).is_ok());
+        // Idle packet
+        let idle = [0x07, 0xFF, 0xC0, 0x00, 0x00, 0x00, 0x55];
+        assert!(SpacePacket::parse(&idle).is_err());
+        assert!(ground.push(&idle).is_ok());
+        // Truncated primary header
+        assert!(ground.push(&[0x08]).is_err());
+        assert!(ground.nb_received_packets() == 0);
+
+        let packets = SpacePacketEncoder::new(FILE_APID)
+            .unwrap()
+            .object_packets(&encoder, 0)
+            .unwrap();
+        for packet in &packets {
+            ground.push(packet).unwrap();
+            ground.push(&foreign).unwrap();
+        }
+        assert!(ground.nb_lost_packets() == 0);
+        assert!(ground.finish().unwrap() == file);
+    }
+}
@@ -0,0 +1,46 @@
+#![cfg(feature = "ffi")]
+
+mod tests {
+
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    /// Build the static library of the raptor-code-ffi package, in a separate target directory
+    fn build_staticlib(manifest_dir: &Path, target_dir: &Path) -> PathBuf {
+        let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+        let status = Command::new(cargo)
+            .current_dir(manifest_dir)
+            .args(["build", "--package", "raptor-code-ffi"])
+            .arg("--target-dir")
+            .arg(target_dir)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        target_dir.join("debug").join("libraptor_code.a")
+    }
+
+    #[test]
+    pub fn test_c_program() {
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("ffi");
+        let staticlib = build_staticlib(manifest_dir, &target_dir);
+
+        let program = target_dir.join("test_c_program");
+        let cc = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+        let status = Command::new(cc)
+            .arg("-Wall")
+            .arg("-Werror")
+            .arg("-I")
+            .arg(manifest_dir.join("include"))
+            .arg(manifest_dir.join("tests").join("ffi").join("test.c"))
+            .arg(&staticlib)
+            .args(["-lpthread", "-ldl", "-lm", "-o"])
+            .arg(&program)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let status = Command::new(&program).status().unwrap();
+        assert!(status.success());
+    }
+}
@@ -0,0 +1,58 @@
+/*
+ * Encode a source block, lose some encoding symbols and decode it with the C API
+ */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "raptor_code.h"
+
+#define SYMBOL_SIZE 64
+#define NB_SOURCE_SYMBOLS 20
+#define SOURCE_BLOCK_LENGTH (SYMBOL_SIZE * NB_SOURCE_SYMBOLS - 10)
+
+#define CHECK(condition)                                          \
+    if (!(condition)) {                                           \
+        fprintf(stderr, "%s:%d: %s\n", __FILE__, __LINE__, #condition); \
+        return 1;                                                 \
+    }
+
+int main(void)
+{
+    uint8_t source_block[SOURCE_BLOCK_LENGTH];
+    uint8_t decoded[SOURCE_BLOCK_LENGTH];
+    uint8_t symbol[SYMBOL_SIZE];
+    size_t symbol_length = 0;
+    uint32_t esi;
+
+    for (size_t i = 0; i < SOURCE_BLOCK_LENGTH; i++) {
+        source_block[i] = (uint8_t)(i * 7 + 3);
+    }
+
+    RaptorEncoder *encoder = raptor_encoder_new(source_block, SOURCE_BLOCK_LENGTH, NB_SOURCE_SYMBOLS);
+    CHECK(encoder != NULL);
+    CHECK(raptor_encoder_nb_source_symbols(encoder) == NB_SOURCE_SYMBOLS);
+    CHECK(raptor_encoder_fountain(encoder, 0, symbol, 1, &symbol_length) == RAPTOR_STATUS_BUFFER_TOO_SMALL);
+    CHECK(symbol_length == SYMBOL_SIZE);
+
+    RaptorDecoder *decoder = raptor_decoder_new(NB_SOURCE_SYMBOLS);
+    CHECK(decoder != NULL);
+    CHECK(raptor_decoder_decode(decoder, decoded, SOURCE_BLOCK_LENGTH) == RAPTOR_STATUS_NOT_DECODABLE);
+
+    /* The first 5 source symbols are lost */
+    for (esi = 5; !raptor_decoder_fully_specified(decoder); esi++) {
+        CHECK(esi < 10 * NB_SOURCE_SYMBOLS);
+        CHECK(raptor_encoder_fountain(encoder, esi, symbol, sizeof(symbol), &symbol_length) == RAPTOR_STATUS_OK);
+        CHECK(raptor_decoder_push(decoder, symbol, symbol_length, esi) == RAPTOR_STATUS_OK);
+    }
+
+    CHECK(raptor_decoder_decode(decoder, decoded, SOURCE_BLOCK_LENGTH) == RAPTOR_STATUS_OK);
+    CHECK(memcmp(source_block, decoded, SOURCE_BLOCK_LENGTH) == 0);
+
+    CHECK(raptor_decoder_push(NULL, symbol, symbol_length, 0) == RAPTOR_STATUS_NULL_POINTER);
+    CHECK(raptor_decoder_new(0) == NULL);
+
+    raptor_decoder_free(decoder);
+    raptor_encoder_free(encoder);
+    return 0;
+}
@@ -0,0 +1,41 @@
+mod tests {
+
+    use std::path::Path;
+    use std::process::Command;
+
+    const TARGET: &str = "thumbv7em-none-eabihf";
+
+    /// Return true when the standard library of `target` is installed
+    fn target_installed(target: &str) -> bool {
+        let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+        let output = Command::new(rustc)
+            .args(["--print", "target-libdir", "--target", target])
+            .output()
+            .unwrap();
+        let libdir = String::from_utf8_lossy(&output.stdout);
+        output.status.success() && Path::new(libdir.trim()).is_dir()
+    }
+
+    #[test]
+    #[ignore = "requires the thumbv7em-none-eabihf target, run with --ignored"]
+    pub fn test_thumbv7em_build() {
+        assert!(
+            target_installed(TARGET),
+            "{TARGET} is not installed, run `rustup target add {TARGET}`"
+        );
+
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("no_std");
+        let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+        let status = Command::new(cargo)
+            .current_dir(manifest_dir)
+            .args(["build", "--lib", "--no-default-features", "--features"])
+            .arg("auth,bytes")
+            .args(["--target", TARGET])
+            .arg("--target-dir")
+            .arg(target_dir)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+}
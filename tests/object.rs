@@ -0,0 +1,54 @@
+#![cfg(feature = "mmap")]
+
+mod tests {
+
+    use rand::RngCore;
+    use std::path::PathBuf;
+
+    pub fn init() {
+        std::env::set_var("RUST_LOG", "debug");
+        env_logger::builder().is_test(true).try_init().ok();
+    }
+
+    fn temp_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("raptor-code-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    pub fn test_mmap_file_encode_decode() {
+        init();
+
+        let mut data = vec![0u8; 3 * 1024 * 1024 + 17];
+        rand::thread_rng().fill_bytes(&mut data);
+
+        let source_path = temp_file("source.bin");
+        let output_path = temp_file("output.bin");
+        std::fs::write(&source_path, &data).unwrap();
+
+        let encoder = raptor_code::ObjectEncoder::open(&source_path, 1024, 1024).unwrap();
+        let oti = *encoder.transmission_information();
+        let mut decoder = raptor_code::ObjectDecoder::create(&output_path, oti).unwrap();
+
+        for sbn in 0..encoder.nb_source_blocks() {
+            let mut block_encoder = encoder.source_block_encoder(sbn).unwrap();
+            let n = block_encoder.nb_source_symbols() + 30;
+            for esi in 0..n {
+                // Simulate loss
+                if esi % 100 == 3 {
+                    continue;
+                }
+                let encoding_symbol = block_encoder.fountain(esi);
+                decoder.push_encoding_symbol(sbn, esi, &encoding_symbol);
+            }
+        }
+
+        assert!(decoder.fully_decoded());
+        decoder.finish().unwrap().flush().unwrap();
+        drop(encoder);
+
+        let output = std::fs::read(&output_path).unwrap();
+        std::fs::remove_file(&source_path).ok();
+        std::fs::remove_file(&output_path).ok();
+        assert!(output == data);
+    }
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "python")]
+
+mod tests {
+
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    /// Build the Python extension module, in a separate target directory
+    fn build_extension_module(manifest_dir: &Path, target_dir: &Path) -> PathBuf {
+        let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+        let status = Command::new(cargo)
+            .current_dir(manifest_dir)
+            .args(["rustc", "--lib", "--crate-type", "cdylib"])
+            .args(["--features", "python,pyo3/extension-module"])
+            .arg("--target-dir")
+            .arg(target_dir)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        // Python imports `raptor_code.so`
+        let module_dir = target_dir.join("python");
+        std::fs::create_dir_all(&module_dir).unwrap();
+        std::fs::copy(
+            target_dir.join("debug").join("libraptor_code.so"),
+            module_dir.join("raptor_code.so"),
+        )
+        .unwrap();
+        module_dir
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    pub fn test_python_module() {
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("python");
+        let module_dir = build_extension_module(manifest_dir, &target_dir);
+
+        let python = std::env::var("PYO3_PYTHON").unwrap_or_else(|_| "python3".to_string());
+        let status = Command::new(python)
+            .env("PYTHONPATH", &module_dir)
+            .arg(
+                manifest_dir
+                    .join("tests")
+                    .join("python")
+                    .join("test_raptor_code.py"),
+            )
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+}
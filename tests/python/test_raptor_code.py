@@ -0,0 +1,102 @@
+"""Loss experiment with the raptor_code Python module"""
+
+import os
+import random
+
+import raptor_code
+
+
+def test_encode_decode_source_block():
+    source_block = os.urandom(20 * 64)
+    encoding_symbols, nb_source_symbols = raptor_code.encode_source_block(source_block, 20, 10)
+    assert nb_source_symbols == 20
+    assert len(encoding_symbols) == 30
+
+    received = [memoryview(symbol) for symbol in encoding_symbols]
+    for esi in random.sample(range(20), 3):
+        received[esi] = None
+
+    decoded = raptor_code.decode_source_block(received, nb_source_symbols, len(source_block))
+    assert decoded == source_block
+
+    # Not enough symbols
+    assert raptor_code.decode_source_block([None] * 30, nb_source_symbols, len(source_block)) is None
+
+
+def test_on_the_fly():
+    source_block = bytearray(os.urandom(100 * 32 - 5))
+    encoder = raptor_code.SourceBlockEncoder(memoryview(source_block), 100)
+    assert encoder.nb_source_symbols == 100
+
+    decoder = raptor_code.SourceBlockDecoder(100)
+    assert decoder.decode(len(source_block)) is None
+
+    esi = 10
+    while not decoder.fully_specified():
+        decoder.push_encoding_symbol(encoder.fountain(esi), esi)
+        esi += 1
+
+    assert decoder.decode(len(source_block)) == source_block
+
+
+def test_writable_buffers():
+    # Writable buffers are copied before the GIL is released
+    source_block = bytearray(os.urandom(16 * 32))
+    encoding_symbols, nb_source_symbols = raptor_code.encode_source_block(source_block, 16, 4)
+    assert raptor_code.encode_source_block(bytes(source_block), 16, 4)[0] == encoding_symbols
+
+    received = [bytearray(symbol) for symbol in encoding_symbols]
+    received[0] = None
+    decoded = raptor_code.decode_source_block(received, nb_source_symbols, len(source_block))
+    assert decoded == source_block
+
+    decoder = raptor_code.SourceBlockDecoder(nb_source_symbols)
+    for esi, symbol in enumerate(encoding_symbols):
+        symbol = bytearray(symbol)
+        decoder.push_encoding_symbol(memoryview(symbol), esi)
+        symbol[:] = bytes(len(symbol))
+    assert decoder.decode(len(source_block)) == source_block
+
+
+def test_small_source_blocks():
+    # Blocks of 2 and 3 source symbols are padded, decoded from the repair symbols only
+    for source_block, max_source_symbols, k in (
+        (b"\x07", 10, 2),
+        (b"\x07\x08", 10, 3),
+        (os.urandom(100), 2, 2),
+        (os.urandom(100), 3, 3),
+    ):
+        encoding_symbols, nb_source_symbols = raptor_code.encode_source_block(source_block, max_source_symbols, 6)
+        assert nb_source_symbols == k
+        received = [None] * k + encoding_symbols[k:]
+        assert raptor_code.decode_source_block(received, k, len(source_block)) == source_block
+
+        encoder = raptor_code.SourceBlockEncoder(source_block, max_source_symbols)
+        assert encoder.nb_source_symbols == k
+        decoder = raptor_code.SourceBlockDecoder(k)
+        esi = k
+        while not decoder.fully_specified():
+            decoder.push_encoding_symbol(encoder.fountain(esi), esi)
+            esi += 1
+        assert decoder.decode(len(source_block)) == source_block
+
+
+def test_invalid_arguments():
+    for call in (
+        lambda: raptor_code.SourceBlockDecoder(0),
+        lambda: raptor_code.SourceBlockEncoder(b"", 10),
+        lambda: raptor_code.encode_source_block(b"data", 10000, 1),
+    ):
+        try:
+            call()
+        except ValueError:
+            continue
+        raise AssertionError("ValueError not raised")
+
+
+if __name__ == "__main__":
+    test_encode_decode_source_block()
+    test_on_the_fly()
+    test_writable_buffers()
+    test_small_source_blocks()
+    test_invalid_arguments()
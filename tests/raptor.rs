@@ -61,15 +61,15 @@ mod tests {
     }
 
     fn on_the_fly_encode(
-        source_block: &Vec<u8>,
+        source_block: &[u8],
         max_source_symbols: usize,
         nb_repair_symbols: u32,
     ) -> Vec<Vec<u8>> {
-        let mut encoder = raptor_code::SourceBlockEncoder::new(&source_block, max_source_symbols);
+        let mut encoder = raptor_code::SourceBlockEncoder::new(source_block, max_source_symbols);
         let n = encoder.nb_source_symbols() + nb_repair_symbols;
 
         let mut encoded_block = Vec::new();
-        for esi in 0..n as u32 {
+        for esi in 0..n {
             let encoding_symbol = encoder.fountain(esi);
             encoded_block.push(encoding_symbol);
         }
@@ -80,7 +80,7 @@ mod tests {
     fn on_the_fly_decode(
         source_block_length: usize,
         nb_source_symbols: usize,
-        encoded_block: &Vec<Option<Vec<u8>>>,
+        encoded_block: &[Option<Vec<u8>>],
     ) -> Option<Vec<u8>> {
         let mut decoder = raptor_code::SourceBlockDecoder::new(nb_source_symbols);
         for (esi, encoding_symbol) in encoded_block.iter().enumerate() {
@@ -93,7 +93,7 @@ mod tests {
         }
 
         assert!(decoder.fully_specified());
-        decoder.decode(source_block_length as usize)
+        decoder.decode(source_block_length)
     }
 
     fn on_the_fly_encode_decode(
@@ -124,6 +124,258 @@ mod tests {
         assert!(decoded_source_block == source_block_data);
     }
 
+    fn on_the_fly_decoder(
+        nb_source_symbols: usize,
+        encoded_block: &[Option<Vec<u8>>],
+    ) -> raptor_code::SourceBlockDecoder {
+        let mut decoder = raptor_code::SourceBlockDecoder::new(nb_source_symbols);
+        for (esi, encoding_symbol) in encoded_block.iter().enumerate() {
+            if let Some(encoding_symbol) = encoding_symbol {
+                decoder.push_encoding_symbol(encoding_symbol, esi as u32);
+            }
+        }
+        decoder
+    }
+
+    #[test]
+    pub fn test_decode_into() {
+        init();
+        let source_block_data = create_source_block_data(10 * 1024 + 5);
+        let encoding_symbols = on_the_fly_encode(&source_block_data, 10, 5);
+        let mut received_encoding_symbols = network_transfer(&encoding_symbols[..11], 0);
+        received_encoding_symbols[0] = None;
+        received_encoding_symbols[1] = None;
+        let mut decoder = on_the_fly_decoder(10, &received_encoding_symbols);
+
+        let mut output = vec![0u8; source_block_data.len()];
+        assert!(!decoder.decode_into(&mut output));
+
+        for esi in [1, 11, 12] {
+            decoder.push_encoding_symbol(&encoding_symbols[esi], esi as u32);
+        }
+        assert!(decoder.decode_into(&mut output));
+        assert!(output == source_block_data);
+    }
+
+    #[test]
+    pub fn test_decode_symbols_into() {
+        init();
+        let source_block_data = create_source_block_data(4 * 1000);
+        let encoding_symbols = on_the_fly_encode(&source_block_data, 4, 3);
+        let mut received_encoding_symbols = network_transfer(&encoding_symbols, 0);
+        received_encoding_symbols[0] = None;
+        received_encoding_symbols[3] = None;
+        let mut decoder = on_the_fly_decoder(4, &received_encoding_symbols);
+
+        let mut symbols = vec![vec![0u8; 1000]; 4];
+        let mut output: Vec<&mut [u8]> = symbols.iter_mut().map(|s| s.as_mut_slice()).collect();
+        assert!(decoder.decode_symbols_into(&mut output));
+        assert!(symbols.concat() == source_block_data);
+    }
+
+    #[test]
+    pub fn test_push_owned_recycle_buffers() {
+        init();
+        let source_block_data = create_source_block_data(64 * 100);
+        let encoding_symbols = on_the_fly_encode(&source_block_data, 64, 20);
+
+        let mut decoder = raptor_code::SourceBlockDecoder::new(64);
+        assert!(decoder.take_recycled_buffer().is_none());
+        for (esi, encoding_symbol) in encoding_symbols.into_iter().enumerate() {
+            decoder.push_owned(encoding_symbol, esi as u32);
+        }
+        assert!(decoder.fully_specified());
+
+        // Symbols received once the block is fully specified are redundant
+        let buffer = decoder.take_recycled_buffer().unwrap();
+        assert!(buffer.is_empty());
+        assert!(buffer.capacity() >= 100);
+
+        let output = decoder.decode(source_block_data.len()).unwrap();
+        assert!(output == source_block_data);
+    }
+
+    #[test]
+    pub fn test_decoder_statistics() {
+        init();
+        let source_block_data = create_source_block_data(32 * 10);
+        let encoding_symbols = on_the_fly_encode(&source_block_data, 32, 10);
+
+        let mut decoder = raptor_code::SourceBlockDecoder::new(32);
+        let stats = decoder.statistics();
+        assert!(stats.nb_received == 0);
+        assert!(stats.rank > 0);
+        assert!(stats.rank + stats.nb_missing > 32);
+
+        decoder.push_encoding_symbol(&encoding_symbols[0], 0);
+        decoder.push_encoding_symbol(&encoding_symbols[0], 0);
+        let stats = decoder.statistics();
+        assert!(stats.nb_received == 2);
+        assert!(stats.nb_duplicate == 1);
+        assert!(stats.nb_useful() == 1);
+
+        for (esi, encoding_symbol) in encoding_symbols.iter().enumerate() {
+            decoder.push_encoding_symbol(encoding_symbol, esi as u32);
+        }
+        let stats = decoder.statistics();
+        assert!(decoder.fully_specified());
+        assert!(stats.nb_missing == 0);
+        assert!(stats.nb_received == 44);
+        assert!(stats.nb_duplicate == 2);
+        assert!(stats.nb_redundant > 0);
+        assert!(stats.nb_useful() + stats.nb_redundant == 42);
+    }
+
+    #[test]
+    pub fn test_checksum_corrupted_symbols() {
+        init();
+        let source_block_data = create_source_block_data(16 * 100);
+        let mut encoder = raptor_code::SourceBlockEncoder::new(&source_block_data, 16)
+            .with_checksum(raptor_code::Checksum::Crc32c);
+        let mut decoder =
+            raptor_code::SourceBlockDecoder::new(16).with_checksum(raptor_code::Checksum::Crc32c);
+
+        let mut esi = 0;
+        while !decoder.fully_specified() {
+            let mut encoding_symbol = encoder.fountain(esi);
+            assert!(encoding_symbol.len() == 100 + raptor_code::Checksum::Crc32c.size());
+            // Corrupt one symbol out of 3
+            if esi % 3 == 0 {
+                encoding_symbol[esi as usize % 100] ^= 0x01;
+            }
+            if esi % 2 == 0 {
+                decoder.push_encoding_symbol(&encoding_symbol, esi);
+            } else {
+                decoder.push_owned(encoding_symbol, esi);
+            }
+            esi += 1;
+        }
+
+        let stats = decoder.statistics();
+        assert!(stats.nb_corrupted == esi.div_ceil(3));
+        let output = decoder.decode(source_block_data.len()).unwrap();
+        assert!(output == source_block_data);
+    }
+
+    #[test]
+    pub fn test_encoding_symbol_groups() {
+        init();
+        // Last source symbol is shorter than the others
+        let source_block_data = create_source_block_data(40 * 32 - 5);
+        let mut encoder = raptor_code::SourceBlockEncoder::new(&source_block_data, 40)
+            .with_checksum(raptor_code::Checksum::Crc32c);
+        let mut decoder =
+            raptor_code::SourceBlockDecoder::new(40).with_checksum(raptor_code::Checksum::Crc32c);
+
+        let g = 4;
+        let packet = encoder.fountain_group(0, g);
+        assert!(packet.len() == 4 * 32 + raptor_code::Checksum::Crc32c.size());
+        assert!(packet[..32] == encoder.fountain(0)[..32]);
+
+        // Lose the first packet, corrupt the third one
+        let mut esi = g;
+        let mut nb_packets = 0;
+        while !decoder.fully_specified() {
+            let mut packet = encoder.fountain_group(esi, g);
+            if esi == 3 * g {
+                packet[40] ^= 0x01;
+            }
+            decoder.push_encoding_symbol_group(&packet, esi, g);
+            esi += g;
+            nb_packets += 1;
+        }
+
+        let stats = decoder.statistics();
+        assert!(stats.nb_received == nb_packets * g);
+        assert!(stats.nb_corrupted == g);
+
+        // Packets that cannot be split into G symbols are discarded
+        let packet = encoder.fountain_group(esi, 3);
+        decoder.push_encoding_symbol_group(&packet, esi, 5);
+        assert!(decoder.statistics().nb_corrupted == g + 5);
+
+        let output = decoder.decode(source_block_data.len()).unwrap();
+        assert!(output == source_block_data);
+    }
+
+    #[test]
+    pub fn test_verify_and_recover_corrupted_symbol() {
+        init();
+        let source_block_data = create_source_block_data(20 * 64);
+        let encoding_symbols = on_the_fly_encode(&source_block_data, 20, 10);
+
+        // Consistent symbols
+        let mut decoder = raptor_code::SourceBlockDecoder::new(20).with_verification();
+        assert!(decoder.verify().is_none());
+        for (esi, encoding_symbol) in encoding_symbols.iter().enumerate() {
+            decoder.push_encoding_symbol(encoding_symbol, esi as u32);
+        }
+        assert!(decoder.verify().unwrap().is_empty());
+        assert!(decoder.recover().unwrap().is_empty());
+
+        // One corrupted source symbol
+        let mut decoder = raptor_code::SourceBlockDecoder::new(20).with_verification();
+        for (esi, encoding_symbol) in encoding_symbols.iter().enumerate() {
+            let mut encoding_symbol = encoding_symbol.clone();
+            if esi == 5 {
+                encoding_symbol[10] ^= 0x40;
+            }
+            decoder.push_encoding_symbol(&encoding_symbol, esi as u32);
+        }
+
+        assert!(!decoder.verify().unwrap().is_empty());
+        assert!(decoder.recover().unwrap() == vec![5]);
+        assert!(decoder.verify().unwrap().is_empty());
+        let output = decoder.decode(source_block_data.len()).unwrap();
+        assert!(output == source_block_data);
+    }
+
+    #[test]
+    pub fn test_decoder_into_encoder() {
+        init();
+        let source_block_data = create_source_block_data(30 * 128);
+        let mut encoder = raptor_code::SourceBlockEncoder::new(&source_block_data, 30)
+            .with_checksum(raptor_code::Checksum::Crc32c);
+        let decoder =
+            raptor_code::SourceBlockDecoder::new(30).with_checksum(raptor_code::Checksum::Crc32c);
+
+        // Not decoded yet, the decoder is given back
+        let Err(mut decoder) = decoder.into_encoder() else {
+            panic!("decoder is not fully specified");
+        };
+
+        // Lose the first source symbols
+        let mut esi = 5;
+        while !decoder.fully_specified() {
+            decoder.push_encoding_symbol(&encoder.fountain(esi), esi);
+            esi += 1;
+        }
+
+        // Regenerate lost source symbols and fresh repair symbols
+        let Ok(mut regenerated) = decoder.into_encoder() else {
+            panic!("decoder is fully specified");
+        };
+        for esi in (0..5).chain(100..110) {
+            assert!(regenerated.fountain(esi) == encoder.fountain(esi));
+        }
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    pub fn test_push_bytes() {
+        init();
+        let source_block_data = create_source_block_data(4 * 1000);
+        let encoding_symbols = on_the_fly_encode(&source_block_data, 4, 3);
+
+        let mut decoder = raptor_code::SourceBlockDecoder::new(4);
+        for (esi, encoding_symbol) in encoding_symbols.into_iter().enumerate().skip(1) {
+            decoder.push_bytes(bytes::Bytes::from(encoding_symbol), esi as u32);
+        }
+
+        let output = decoder.decode(source_block_data.len()).unwrap();
+        assert!(output == source_block_data);
+    }
+
     #[test]
     pub fn test_encode_decode_100k_repair100_loss5() {
         init();
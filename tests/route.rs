@@ -0,0 +1,177 @@
+mod tests {
+
+    use rand::RngCore;
+    use raptor_code::alc::LctHeader;
+    use raptor_code::route::{
+        RepairFlow, RouteDecoder, RouteEncoder, SourcePacket, CODEPOINT_MEDIA_SEGMENT_FILE,
+    };
+
+    pub fn init() {
+        std::env::set_var("RUST_LOG", "debug");
+        env_logger::builder().is_test(true).try_init().ok();
+    }
+
+    const SOURCE_TSI: u64 = 10;
+    const REPAIR_TSI: u64 = 11;
+
+    fn segment(length: usize) -> Vec<u8> {
+        let mut segment = vec![0u8; length];
+        rand::thread_rng().fill_bytes(&mut segment);
+        segment
+    }
+
+    #[test]
+    pub fn test_route_media_segments() {
+        init();
+
+        let repair = RepairFlow::new(REPAIR_TSI, 512, 64, 25).unwrap();
+        let encoder = RouteEncoder::new(SOURCE_TSI, CODEPOINT_MEDIA_SEGMENT_FILE, 1024)
+            .unwrap()
+            .with_repair_flow(repair)
+            .unwrap();
+        let mut decoder = RouteDecoder::new(SOURCE_TSI).with_repair_flow(repair);
+
+        // Segments of a few hundred KiB, the source flow loses 1 packet out of 12
+        // and the repair flow 1 packet out of 5
+        let segments: Vec<Vec<u8>> = [150_000, 97_313, 1, 220_101, 64_000]
+            .iter()
+            .map(|length| segment(*length))
+            .collect();
+        let mut delivered = Vec::new();
+        for (toi, segment) in segments.iter().enumerate() {
+            let toi = toi as u64 + 1;
+            for (i, packet) in encoder
+                .source_packets(toi, segment)
+                .unwrap()
+                .iter()
+                .enumerate()
+            {
+                if i % 12 != 5 {
+                    decoder.push(packet).unwrap();
+                }
+            }
+            for (i, packet) in encoder
+                .repair_packets(toi, segment)
+                .unwrap()
+                .iter()
+                .enumerate()
+            {
+                if i % 5 != 2 {
+                    decoder.push(packet).unwrap();
+                }
+            }
+            delivered.extend(decoder.take_objects());
+        }
+
+        assert!(delivered.len() == segments.len());
+        for (object, segment) in delivered.iter().zip(&segments) {
+            assert!(object.codepoint == Some(CODEPOINT_MEDIA_SEGMENT_FILE));
+            assert!(&object.data == segment);
+        }
+    }
+
+    #[test]
+    pub fn test_route_repair_first() {
+        init();
+
+        // Repair packets received before the source packets, only half of the source packets are received
+        let repair = RepairFlow::new(REPAIR_TSI, 256, 128, 60).unwrap();
+        let encoder = RouteEncoder::new(SOURCE_TSI, CODEPOINT_MEDIA_SEGMENT_FILE, 1280)
+            .unwrap()
+            .with_repair_flow(repair)
+            .unwrap();
+        let mut decoder = RouteDecoder::new(SOURCE_TSI).with_repair_flow(repair);
+
+        let segment = segment(50_000);
+        for packet in encoder.repair_packets(3, &segment).unwrap() {
+            decoder.push(&packet).unwrap();
+        }
+        assert!(decoder.take_objects().is_empty());
+        for packet in encoder
+            .source_packets(3, &segment)
+            .unwrap()
+            .iter()
+            .step_by(2)
+        {
+            decoder.push(packet).unwrap();
+        }
+
+        let objects = decoder.take_objects();
+        assert!(objects.len() == 1);
+        assert!(objects[0].toi == 3);
+        assert!(objects[0].data == segment);
+    }
+
+    #[test]
+    pub fn test_route_source_flow_only() {
+        init();
+
+        let encoder = RouteEncoder::new(SOURCE_TSI, CODEPOINT_MEDIA_SEGMENT_FILE, 1400).unwrap();
+        let mut decoder = RouteDecoder::new(SOURCE_TSI);
+        let segment = segment(10_000);
+        assert!(encoder.repair_packets(1, &segment).unwrap().is_empty());
+
+        // Packets received out of order, the object is delivered with the last missing packet
+        let mut packets = encoder.source_packets(1, &segment).unwrap();
+        packets.reverse();
+        for packet in &packets {
+            assert!(decoder.take_objects().is_empty());
+            decoder.push(packet).unwrap();
+        }
+        let objects = decoder.take_objects();
+        assert!(objects.len() == 1);
+        assert!(objects[0].data == segment);
+    }
+
+    #[test]
+    pub fn test_route_overlapping_packets() {
+        init();
+
+        let segment = segment(32);
+        let header = LctHeader {
+            tsi: SOURCE_TSI,
+            toi: 1,
+            codepoint: CODEPOINT_MEDIA_SEGMENT_FILE,
+            transfer_object_length: Some(32),
+            ..Default::default()
+        };
+        let packet = |start: usize, end: usize| {
+            SourcePacket::new(header.clone(), start as u32, &segment[start..end])
+                .to_bytes()
+                .unwrap()
+        };
+
+        // 32 bytes are received but 24..32 is missing
+        let mut decoder = RouteDecoder::new(SOURCE_TSI);
+        decoder.push(&packet(0, 16)).unwrap();
+        decoder.push(&packet(8, 24)).unwrap();
+        assert!(decoder.take_objects().is_empty());
+        decoder.push(&packet(24, 32)).unwrap();
+        let objects = decoder.take_objects();
+        assert!(objects.len() == 1);
+        assert!(objects[0].data == segment);
+
+        // The object is re-packetized with another payload size by a second sender
+        let repair = RepairFlow::new(REPAIR_TSI, 8, 8, 50).unwrap();
+        let first = RouteEncoder::new(SOURCE_TSI, CODEPOINT_MEDIA_SEGMENT_FILE, 16)
+            .unwrap()
+            .with_repair_flow(repair)
+            .unwrap();
+        let second = RouteEncoder::new(SOURCE_TSI, CODEPOINT_MEDIA_SEGMENT_FILE, 24)
+            .unwrap()
+            .with_repair_flow(repair)
+            .unwrap();
+        let mut decoder = RouteDecoder::new(SOURCE_TSI).with_repair_flow(repair);
+        let first_packets = first.source_packets(2, &segment).unwrap();
+        let second_packets = second.source_packets(2, &segment).unwrap();
+        decoder.push(&first_packets[0]).unwrap();
+        decoder.push(&second_packets[0]).unwrap();
+        decoder.push(&first_packets[0]).unwrap();
+        assert!(decoder.take_objects().is_empty());
+        decoder.push(&second_packets[1]).unwrap();
+        let objects = decoder.take_objects();
+        assert!(objects.len() == 1);
+        assert!(objects[0].toi == 2);
+        assert!(objects[0].data == segment);
+    }
+}
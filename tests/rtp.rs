@@ -0,0 +1,115 @@
+mod tests {
+
+    use rand::{Rng, RngCore};
+    use raptor_code::rtp::{RtpFecDecoder, RtpFecEncoder, RtpHeader};
+
+    pub fn init() {
+        std::env::set_var("RUST_LOG", "debug");
+        env_logger::builder().is_test(true).try_init().ok();
+    }
+
+    const SYMBOL_SIZE: u16 = 128;
+
+    /// Synthetic RTP packet with a random payload
+    fn rtp_packet(ssrc: u32, sequence_number: u16, payload_size: usize) -> Vec<u8> {
+        let header = RtpHeader {
+            marker: sequence_number.is_multiple_of(30),
+            payload_type: 96,
+            sequence_number,
+            timestamp: sequence_number as u32 * 3000,
+            ssrc,
+        };
+        let mut packet = Vec::new();
+        header.write(&mut packet);
+        let mut payload = vec![0u8; payload_size];
+        rand::thread_rng().fill_bytes(&mut payload);
+        packet.extend_from_slice(&payload);
+        packet
+    }
+
+    #[test]
+    pub fn test_rtp_flows() {
+        init();
+
+        let video = 0x1111;
+        let audio = 0x2222;
+        let mut encoder = RtpFecEncoder::new(100, 0x3333, SYMBOL_SIZE, 64, 28).unwrap();
+        let mut decoder = RtpFecDecoder::new(SYMBOL_SIZE).unwrap();
+        for ssrc in [video, audio] {
+            assert!(encoder.add_source_flow(ssrc) == decoder.add_source_flow(ssrc));
+        }
+
+        // Interleaved video and audio packets, the repair packets are sent as soon as a block is complete.
+        // At most 2 packets of 10 symbols are lost per block, one repair packet per block is lost as well
+        let mut rng = rand::thread_rng();
+        let mut sent = Vec::new();
+        let mut lost = Vec::new();
+        let mut received = Vec::new();
+        let mut nb_repair = 0;
+        for seq in 0..300u16 {
+            let packet = match seq % 3 {
+                0 => rtp_packet(audio, seq / 3, 160),
+                _ => rtp_packet(video, seq, rng.gen_range(20..1200)),
+            };
+            sent.push(packet.clone());
+            let source_packet = encoder.protect(&packet).unwrap();
+            if seq == 299 {
+                encoder.finish_block();
+            }
+
+            if seq % 7 == 3 {
+                lost.push(packet);
+            } else {
+                received.push(decoder.receive_source(&source_packet).unwrap().to_vec());
+            }
+
+            for (i, repair_packet) in encoder.take_repair_packets().iter().enumerate() {
+                nb_repair += 1;
+                if i != 5 {
+                    decoder.receive_repair(repair_packet).unwrap();
+                }
+            }
+        }
+        assert!(nb_repair > 0);
+
+        let mut recovered = decoder.take_recovered_packets();
+        log::info!(
+            "{} packets sent, {} lost, {} recovered, {} repair packets",
+            sent.len(),
+            lost.len(),
+            recovered.len(),
+            nb_repair
+        );
+        recovered.sort();
+        lost.sort();
+        assert!(recovered == lost);
+
+        // Every packet of both flows is delivered
+        received.extend(recovered);
+        received.sort();
+        sent.sort();
+        assert!(received == sent);
+    }
+
+    #[test]
+    pub fn test_rtp_invalid_packets() {
+        init();
+
+        let mut encoder = RtpFecEncoder::new(100, 0x3333, SYMBOL_SIZE, 8, 2).unwrap();
+        let mut decoder = RtpFecDecoder::new(SYMBOL_SIZE).unwrap();
+        encoder.add_source_flow(1).unwrap();
+        decoder.add_source_flow(1).unwrap();
+
+        // Larger than a source block
+        assert!(encoder.protect(&rtp_packet(1, 0, 2000)).is_err());
+        // Not an RTP packet
+        assert!(encoder.protect(&[0u8; 20]).is_err());
+        assert!(decoder.receive_source(&[0x80, 0, 0]).is_err());
+        assert!(decoder.receive_repair(&rtp_packet(2, 0, 3)).is_err());
+
+        // Source packets from an unknown flow are rejected
+        let source_packet = encoder.protect(&rtp_packet(1, 0, 100)).unwrap();
+        let mut decoder = RtpFecDecoder::new(SYMBOL_SIZE).unwrap();
+        assert!(decoder.receive_source(&source_packet).is_err());
+    }
+}